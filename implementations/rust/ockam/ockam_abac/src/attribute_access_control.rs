@@ -16,7 +16,9 @@ use crate::{eval, Env, Expr};
 use ockam_core::compat::format;
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
-use ockam_identity::{Identifier, IdentitiesRepository, IdentitySecureChannelLocalInfo};
+use ockam_identity::{
+    AttributesEntry, Identifier, IdentitiesRepository, IdentitySecureChannelLocalInfo,
+};
 
 /// This AccessControl uses a storage for authenticated attributes in order
 /// to verify if a policy expression is valid
@@ -70,58 +72,9 @@ where {
 impl AbacAccessControl {
     /// Returns true if the identity is authorized
     pub async fn is_identity_authorized(&self, id: Identifier) -> Result<bool> {
+        let attributes = self.repository.get_attributes(&id).await?;
         let mut environment = self.environment.clone();
-
-        // Get identity attributes and populate the environment:
-        if let Some(attrs) = self.repository.get_attributes(&id).await? {
-            for (key, value) in attrs.attrs() {
-                let key = match from_utf8(key) {
-                    Ok(key) => key,
-                    Err(_) => {
-                        log::warn! {
-                            policy = %self.expression,
-                            id     = %id,
-                            "attribute key is not utf-8"
-                        }
-                        continue;
-                    }
-                };
-                if key.find(|c: char| c.is_whitespace()).is_some() {
-                    log::warn! {
-                        policy = %self.expression,
-                        id     = %id,
-                        key    = %key,
-                        "attribute key with whitespace ignored"
-                    }
-                }
-                match str::from_utf8(value) {
-                    Ok(s) => {
-                        if environment.contains(key) {
-                            log::debug! {
-                                policy = %self.expression,
-                                id     = %id,
-                                key    = %key,
-                                "attribute already present"
-                            }
-                        } else {
-                            environment.put(format!("subject.{key}"), str(s.to_string()));
-                        }
-                    }
-                    Err(e) => {
-                        log::warn! {
-                            policy = %self.expression,
-                            id     = %id,
-                            key    = %key,
-                            err    = %e,
-                            "failed to interpret attribute as string"
-                        }
-                    }
-                }
-            }
-        };
-
-        // add the identifier itself as a subject parameter
-        environment.put("subject.identifier", str(id.to_string()));
+        put_subject(&mut environment, &self.expression, &id, attributes.as_ref());
 
         // Finally, evaluate the expression and return the result:
         match eval(&self.expression, &environment) {
@@ -156,6 +109,67 @@ impl AbacAccessControl {
     }
 }
 
+/// Populate an environment with the identifier of a subject, as `subject.identifier`,
+/// and with its attributes, as `subject.<name>`.
+///
+/// The policy expression is only used for logging.
+pub(crate) fn put_subject(
+    environment: &mut Env,
+    expression: &Expr,
+    id: &Identifier,
+    attributes: Option<&AttributesEntry>,
+) {
+    if let Some(attrs) = attributes {
+        for (key, value) in attrs.attrs() {
+            let key = match from_utf8(key) {
+                Ok(key) => key,
+                Err(_) => {
+                    log::warn! {
+                        policy = %expression,
+                        id     = %id,
+                        "attribute key is not utf-8"
+                    }
+                    continue;
+                }
+            };
+            if key.find(|c: char| c.is_whitespace()).is_some() {
+                log::warn! {
+                    policy = %expression,
+                    id     = %id,
+                    key    = %key,
+                    "attribute key with whitespace ignored"
+                }
+            }
+            match str::from_utf8(value) {
+                Ok(s) => {
+                    if environment.contains(key) {
+                        log::debug! {
+                            policy = %expression,
+                            id     = %id,
+                            key    = %key,
+                            "attribute already present"
+                        }
+                    } else {
+                        environment.put(format!("subject.{key}"), str(s.to_string()));
+                    }
+                }
+                Err(e) => {
+                    log::warn! {
+                        policy = %expression,
+                        id     = %id,
+                        key    = %key,
+                        err    = %e,
+                        "failed to interpret attribute as string"
+                    }
+                }
+            }
+        }
+    }
+
+    // add the identifier itself as a subject parameter
+    environment.put("subject.identifier", str(id.to_string()));
+}
+
 #[async_trait]
 impl IncomingAccessControl for AbacAccessControl {
     /// Returns true if the sender of the message is validated by the expression stored in AbacAccessControl
@@ -3,6 +3,7 @@ use core::cmp::Ordering;
 use crate::env::Env;
 use crate::error::EvalError;
 use crate::expr::{unit, Expr};
+use ockam_core::compat::format;
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::vec::Vec;
 
@@ -40,6 +41,7 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
             Op::Eval(Expr::List(xs))  => match &xs[..] {
                 []                    => args.push(unit()),
                 [Expr::Ident(id), ..] => {
+                    check_operator(id, &xs[1 ..])?;
                     let nargs = xs.len() - 1; // number of arguments
                     match id.as_str() {
                         "and" => {
@@ -82,16 +84,8 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                             }
                             continue
                         }
-                        "not" => {
-                            if nargs != 1 {
-                                return Err(EvalError::malformed("'not' requires one argument"))
-                            }
-                            ctrl.push(Op::Not)
-                        }
+                        "not" => ctrl.push(Op::Not),
                         "if" => {
-                            if nargs != 3 {
-                                return Err(EvalError::malformed("'if' requires three arguments"))
-                            }
                             // We first evaluate the test and only then, depending on the result
                             // do we either evaluate the true branch or the false branch.
                             ctrl.push(Op::Eval(&xs[3])); // false branch
@@ -100,52 +94,20 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                             ctrl.push(Op::Eval(&xs[1])); // test
                             continue
                         }
-                        "<" => {
-                            if nargs < 2 {
-                                let msg = "'<' requires at least two arguments";
-                                return Err(EvalError::malformed(msg))
-                            }
-                            ctrl.push(Op::Lt(nargs))
-                        }
-                        ">" => {
-                            if nargs < 2 {
-                                let msg = "'>' requires at least two arguments";
-                                return Err(EvalError::malformed(msg))
-                            }
-                            ctrl.push(Op::Gt(nargs))
-                        }
-                        "=" => {
-                            if nargs < 2 {
-                                let msg = "'=' requires at least two arguments";
-                                return Err(EvalError::malformed(msg))
-                            }
-                            ctrl.push(Op::Eq(nargs))
-                        }
+                        "<" => ctrl.push(Op::Lt(nargs)),
+                        ">" => ctrl.push(Op::Gt(nargs)),
+                        "=" => ctrl.push(Op::Eq(nargs)),
                         "!=" => {
                             ctrl.push(Op::Not);
                             ctrl.push(Op::Eq(nargs))
                         }
-                        "member?" => {
-                            if nargs != 2 {
-                                let msg = "'member?' requires two arguments";
-                                return Err(EvalError::malformed(msg))
-                            }
-                            ctrl.push(Op::Member)
-                        }
+                        "member?" => ctrl.push(Op::Member),
                         "exists?" => {
-                            let mut b = true;
-                            for x in &xs[1 ..] {
-                                match x {
-                                    Expr::Ident(id) => if !env.contains(id) {
-                                        b = false;
-                                        break
-                                    }
-                                    other => {
-                                        let msg = "'exists?' expects identifiers as arguments";
-                                        return Err(EvalError::InvalidType(other.clone(), msg))
-                                    }
-                                }
-                            }
+                            // the arguments are identifiers, as checked by `check_operator`
+                            let b = xs[1 ..].iter().all(|x| match x {
+                                Expr::Ident(id) => env.contains(id),
+                                _               => false
+                            });
                             args.push(Expr::Bool(b));
                             continue
                         }
@@ -266,6 +228,33 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
     Ok(pop(&mut args))
 }
 
+/// Check that `op` is a known operator, applied to the number and kind
+/// of arguments it expects.
+///
+/// This is the single definition of the operators arity, used both when
+/// evaluating an expression and when validating it with `Expr::validate`.
+pub(crate) fn check_operator(op: &str, args: &[Expr]) -> Result<(), EvalError> {
+    let nargs = args.len();
+    match op {
+        "and" | "or" | "!=" => Ok(()),
+        "not" if nargs != 1 => Err(EvalError::malformed("'not' requires one argument")),
+        "if" if nargs != 3 => Err(EvalError::malformed("'if' requires three arguments")),
+        "<" | ">" | "=" if nargs < 2 => Err(EvalError::malformed(format!(
+            "'{op}' requires at least two arguments"
+        ))),
+        "member?" if nargs != 2 => Err(EvalError::malformed("'member?' requires two arguments")),
+        "exists?" => match args.iter().find(|x| !x.is_ident()) {
+            Some(other) => {
+                let msg = "'exists?' expects identifiers as arguments";
+                Err(EvalError::InvalidType(other.clone(), msg))
+            }
+            None => Ok(()),
+        },
+        "not" | "if" | "<" | ">" | "=" | "member?" => Ok(()),
+        _ => Err(EvalError::Unknown(op.to_string())),
+    }
+}
+
 /// Pop off the topmost stack value.
 ///
 /// # Panics
@@ -0,0 +1,219 @@
+use crate::attribute_access_control::put_subject;
+use crate::expr::str;
+use crate::traits::PolicyStorage;
+use crate::types::{Action, Resource};
+use crate::{eval, Env, Expr};
+use ockam_core::compat::format;
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::Arc;
+use ockam_core::Result;
+use ockam_identity::{AttributesEntry, Identifier};
+
+/// The outcome of a policy evaluation, with the reason for that outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyDecision {
+    allowed: bool,
+    reason: String,
+}
+
+impl PolicyDecision {
+    fn allow(reason: impl Into<String>) -> Self {
+        Self {
+            allowed: true,
+            reason: reason.into(),
+        }
+    }
+
+    fn deny(reason: impl Into<String>) -> Self {
+        Self {
+            allowed: false,
+            reason: reason.into(),
+        }
+    }
+
+    /// Return true if access is allowed
+    pub fn is_allowed(&self) -> bool {
+        self.allowed
+    }
+
+    /// Return the reason for this decision
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+/// Evaluates the stored policy of a resource and action against subject attributes.
+///
+/// Contrary to [`crate::PolicyAccessControl`], the attributes are given by the caller
+/// instead of being retrieved for the sender of a message.
+pub struct PolicyEvaluator {
+    policies: Arc<dyn PolicyStorage>,
+    fallback: Option<Expr>,
+}
+
+impl PolicyEvaluator {
+    /// Create a new evaluator. Access is denied when no policy is stored for a resource and action
+    pub fn new(policies: Arc<dyn PolicyStorage>) -> Self {
+        Self {
+            policies,
+            fallback: None,
+        }
+    }
+
+    /// Use the given expression when no policy is stored for a resource and action
+    pub fn with_fallback(mut self, fallback: Expr) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// Return the policy stored for a resource and action, or the fallback expression
+    pub async fn get_policy_with_fallback(&self, r: &Resource, a: &Action) -> Result<Option<Expr>> {
+        Ok(self
+            .policies
+            .get_policy(r, a)
+            .await?
+            .or_else(|| self.fallback.clone()))
+    }
+
+    /// Evaluate the policy of a resource and action against the given subject and its attributes.
+    ///
+    /// The subject is available in the policy expression as `subject.identifier`, and its
+    /// attributes as `subject.<name>`, exactly as with [`crate::AbacAccessControl`].
+    /// The expression can also refer to `resource.id` and `action.id`.
+    pub async fn evaluate(
+        &self,
+        r: &Resource,
+        a: &Action,
+        subject: &Identifier,
+        attributes: &AttributesEntry,
+    ) -> Result<PolicyDecision> {
+        let expr = match self.get_policy_with_fallback(r, a).await? {
+            Some(expr) => expr,
+            None => return Ok(PolicyDecision::deny(format!("no policy found for {r}:{a}"))),
+        };
+
+        let mut env = Env::new();
+        env.put("resource.id", str(r.as_str()));
+        env.put("action.id", str(a.as_str()));
+        put_subject(&mut env, &expr, subject, Some(attributes));
+
+        let decision = match eval(&expr, &env) {
+            Ok(Expr::Bool(true)) => PolicyDecision::allow(format!("policy {expr} is satisfied")),
+            Ok(Expr::Bool(false)) => {
+                PolicyDecision::deny(format!("policy {expr} is not satisfied"))
+            }
+            Ok(other) => PolicyDecision::deny(format!(
+                "policy {expr} did not yield a boolean result: {other}"
+            )),
+            Err(e) => PolicyDecision::deny(format!("policy {expr} can't be evaluated: {e}")),
+        };
+        Ok(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::Memory;
+    use crate::parser::parse;
+    use ockam_core::compat::collections::BTreeMap;
+    use ockam_identity::models::TimestampInSeconds;
+
+    const SUBJECT: &str = "Ie86be15e83d1c93e24dd1967010b01b6df491b45";
+
+    fn subject() -> Identifier {
+        Identifier::try_from(SUBJECT).unwrap()
+    }
+
+    fn attributes(name: &str) -> AttributesEntry {
+        let attrs = BTreeMap::from([(b"name".to_vec(), name.as_bytes().to_vec())]);
+        AttributesEntry::new(attrs, TimestampInSeconds(0), None, None)
+    }
+
+    async fn evaluator() -> PolicyEvaluator {
+        let policies = Arc::new(Memory::new());
+        let policy = parse(r#"(= subject.name "John")"#).unwrap().unwrap();
+        policies
+            .set_policy(&Resource::new("r"), &Action::new("a"), &policy)
+            .await
+            .unwrap();
+        PolicyEvaluator::new(policies)
+    }
+
+    #[tokio::test]
+    async fn test_allow() {
+        let decision = evaluator()
+            .await
+            .evaluate(
+                &Resource::new("r"),
+                &Action::new("a"),
+                &subject(),
+                &attributes("John"),
+            )
+            .await
+            .unwrap();
+        assert!(decision.is_allowed());
+    }
+
+    #[tokio::test]
+    async fn test_subject_identifier() {
+        let policies = Arc::new(Memory::new());
+        let (r, a) = (Resource::new("r"), Action::new("a"));
+        let policy = parse(&format!(r#"(= subject.identifier "{SUBJECT}")"#))
+            .unwrap()
+            .unwrap();
+        policies.set_policy(&r, &a, &policy).await.unwrap();
+        let evaluator = PolicyEvaluator::new(policies);
+
+        let decision = evaluator
+            .evaluate(&r, &a, &subject(), &attributes("John"))
+            .await
+            .unwrap();
+        assert!(decision.is_allowed());
+
+        let other = Identifier::try_from("I47e4ae35f3b6f6b8e1e4bfc82a4c7b4c2e5d8f61").unwrap();
+        let decision = evaluator
+            .evaluate(&r, &a, &other, &attributes("John"))
+            .await
+            .unwrap();
+        assert!(!decision.is_allowed());
+    }
+
+    #[tokio::test]
+    async fn test_deny() {
+        let decision = evaluator()
+            .await
+            .evaluate(
+                &Resource::new("r"),
+                &Action::new("a"),
+                &subject(),
+                &attributes("Jane"),
+            )
+            .await
+            .unwrap();
+        assert!(!decision.is_allowed());
+        assert!(decision.reason().contains("not satisfied"));
+    }
+
+    #[tokio::test]
+    async fn test_no_policy() {
+        let evaluator = evaluator().await;
+        let (r, a) = (Resource::new("r"), Action::new("other"));
+
+        // access is denied by default
+        let decision = evaluator
+            .evaluate(&r, &a, &subject(), &attributes("John"))
+            .await
+            .unwrap();
+        assert!(!decision.is_allowed());
+        assert!(decision.reason().contains("no policy found"));
+
+        // unless a fallback expression is provided
+        let evaluator = evaluator.with_fallback(Expr::Bool(true));
+        let decision = evaluator
+            .evaluate(&r, &a, &subject(), &attributes("John"))
+            .await
+            .unwrap();
+        assert!(decision.is_allowed());
+    }
+}
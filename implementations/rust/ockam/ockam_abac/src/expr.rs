@@ -1,3 +1,4 @@
+use crate::eval::check_operator;
 use crate::EvalError;
 #[cfg(feature = "std")]
 use crate::ParseError;
@@ -147,6 +148,37 @@ impl Expr {
     }
 }
 
+impl Expr {
+    /// Check that this expression is well-formed, i.e. that it only uses known
+    /// operators with the number and kind of arguments they expect.
+    /// The operators are checked exactly as `eval` checks them.
+    ///
+    /// This does not evaluate the expression, so identifiers do not need to be bound.
+    pub fn validate(&self) -> Result<(), EvalError> {
+        let mut ctrl = vec![self];
+
+        while let Some(x) = ctrl.pop() {
+            match x {
+                Expr::Seq(xs) => ctrl.extend(xs.iter()),
+                Expr::List(xs) => match &xs[..] {
+                    [] => {}
+                    [Expr::Ident(id), args @ ..] => {
+                        check_operator(id, args)?;
+                        ctrl.extend(args.iter())
+                    }
+                    [other, ..] => {
+                        let msg = "expected (op ...)";
+                        return Err(EvalError::InvalidType(other.clone(), msg));
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl From<bool> for Expr {
     fn from(b: bool) -> Self {
         Self::Bool(b)
@@ -568,6 +600,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn validate() {
+        for valid in [
+            "true",
+            "subject.name",
+            "()",
+            r#"(and (= resource.version "1.0.0") (member? "John" resource.admins))"#,
+            "(if (exists? subject.age) (> subject.age 18) false)",
+            "[1 (not false)]",
+        ] {
+            let x = parse(valid).unwrap().unwrap();
+            assert!(x.validate().is_ok(), "{valid} should be valid")
+        }
+        for invalid in [
+            "(unknown 1 2)",
+            "(and (unknown 1 2))",
+            "(not true false)",
+            "(if true false)",
+            "(= 1)",
+            "(member? 1)",
+            "(exists? 1)",
+            "(1 2)",
+            "[(= 1)]",
+        ] {
+            let x = parse(invalid).unwrap().unwrap();
+            let Err(error) = x.validate() else {
+                panic!("{invalid} should be invalid")
+            };
+            // validation and evaluation report the same error
+            let expected = eval(&x, &Env::new()).unwrap_err();
+            assert_eq!(error.to_string(), expected.to_string())
+        }
+        assert!(parse(EVIL).unwrap().unwrap().validate().is_ok());
+    }
+
     #[test]
     fn malformed() {
         fn property(s: S) {
@@ -9,6 +9,7 @@ extern crate alloc;
 mod env;
 mod error;
 mod eval;
+mod evaluator;
 mod policy;
 mod traits;
 mod types;
@@ -25,6 +26,7 @@ pub use attribute_access_control::AbacAccessControl;
 pub use env::Env;
 pub use error::{EvalError, ParseError};
 pub use eval::eval;
+pub use evaluator::{PolicyDecision, PolicyEvaluator};
 pub use expr::Expr;
 pub use policy::PolicyAccessControl;
 pub use traits::PolicyStorage;
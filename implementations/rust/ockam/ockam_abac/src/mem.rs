@@ -47,6 +47,10 @@ impl Inner {
         }
     }
 
+    fn del_policies(&mut self, r: &Resource) -> usize {
+        self.policies.remove(r).map(|p| p.len()).unwrap_or(0)
+    }
+
     fn get_policy(&self, r: &Resource, a: &Action) -> Option<Expr> {
         self.policies.get(r).and_then(|p| p.get(a).cloned())
     }
@@ -76,11 +80,16 @@ impl PolicyStorage for Memory {
         Ok(())
     }
 
+    async fn del_policies(&self, r: &Resource) -> Result<usize> {
+        Ok(self.inner.write().unwrap().del_policies(r))
+    }
+
     async fn get_policy(&self, r: &Resource, a: &Action) -> Result<Option<Expr>> {
         Ok(self.inner.read().unwrap().get_policy(r, a))
     }
 
     async fn set_policy(&self, r: &Resource, a: &Action, p: &Expr) -> Result<()> {
+        p.validate()?;
         self.inner.write().unwrap().set_policy(r, a, p);
         Ok(())
     }
@@ -97,6 +106,7 @@ mod tests {
     use crate::expr::{int, seq, str};
     use crate::mem::Memory;
     use crate::parser::parse;
+    use crate::traits::PolicyStorage;
     use crate::types::{Action, Resource};
 
     #[test]
@@ -131,4 +141,50 @@ mod tests {
             .unwrap();
         assert!(eval(&policy, &e).unwrap().is_true())
     }
+
+    #[test]
+    fn del_policies() {
+        let resource = Resource::new("/foo/bar/baz");
+        let other = Resource::new("/foo/bar");
+        let store = Memory::new();
+        {
+            let mut inner = store.inner.write().unwrap();
+            for action in ["r", "w", "x"] {
+                inner.set_policy(&resource, &Action::new(action), &int(1));
+            }
+            inner.set_policy(&other, &Action::new("r"), &int(1));
+        }
+
+        let mut inner = store.inner.write().unwrap();
+        assert_eq!(inner.del_policies(&resource), 3);
+        assert!(inner.policies(&resource).is_empty());
+        assert_eq!(inner.policies(&other).len(), 1);
+        assert_eq!(inner.del_policies(&resource), 0);
+    }
+
+    #[tokio::test]
+    async fn set_policy_rejects_invalid_expressions() {
+        let resource = Resource::new("/foo/bar/baz");
+        let action = Action::new("r");
+        let store = Memory::new();
+
+        let invalid = parse("(unknown subject.name)").unwrap().unwrap();
+        assert!(store
+            .set_policy(&resource, &action, &invalid)
+            .await
+            .is_err());
+        assert!(store
+            .get_policy(&resource, &action)
+            .await
+            .unwrap()
+            .is_none());
+
+        let valid = parse(r#"(= subject.name "John")"#).unwrap().unwrap();
+        store.set_policy(&resource, &action, &valid).await.unwrap();
+        assert!(store
+            .get_policy(&resource, &action)
+            .await
+            .unwrap()
+            .is_some());
+    }
 }
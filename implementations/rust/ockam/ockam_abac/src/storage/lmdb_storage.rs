@@ -33,6 +33,7 @@ impl PolicyStorage for LmdbStorage {
     }
 
     async fn set_policy(&self, r: &Resource, a: &Action, c: &Expr) -> Result<()> {
+        c.validate()?;
         let v = minicbor::to_vec(PolicyEntry {
             expr: Cow::Borrowed(c),
         })?;
@@ -49,6 +49,7 @@ impl PolicyStorage for SqliteStorage {
     }
 
     async fn set_policy(&self, r: &Resource, a: &Action, c: &Expr) -> Result<()> {
+        c.validate()?;
         let conn = self.conn();
         let r = r.clone();
         let a = a.clone();
@@ -83,6 +84,17 @@ impl PolicyStorage for SqliteStorage {
         spawn_blocking(t).await.map_err(map_join_err)?
     }
 
+    async fn del_policies(&self, r: &Resource) -> Result<usize> {
+        let conn = self.conn();
+        let r = r.clone();
+        let t = move || {
+            let conn = conn.lock().unwrap();
+            conn.execute("DELETE FROM policy WHERE resource = ?1;", params![r])
+                .map_err(map_sqlite_err)
+        };
+        spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
     async fn policies(&self, r: &Resource) -> Result<Vec<(Action, Expr)>> {
         let conn = self.conn();
         let r = r.clone();
@@ -163,4 +175,25 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_del_policies() -> Result<()> {
+        let temp_path = NamedTempFile::new().unwrap().into_temp_path();
+        let db = SqliteStorage::new(temp_path.to_path_buf()).await?;
+
+        let r = Resource::from("1");
+        let other = Resource::from("2");
+        let e = Expr::from_str("345")?;
+        for a in ["a", "b", "c"] {
+            db.set_policy(&r, &Action::from(a), &e).await?;
+        }
+        db.set_policy(&other, &Action::from("a"), &e).await?;
+
+        assert_eq!(db.del_policies(&r).await?, 3);
+        assert!(db.policies(&r).await?.is_empty());
+        assert_eq!(db.policies(&other).await?.len(), 1);
+        assert_eq!(db.del_policies(&r).await?, 0);
+
+        Ok(())
+    }
 }
@@ -7,7 +7,17 @@ use ockam_core::Result;
 #[async_trait]
 pub trait PolicyStorage: Send + Sync + 'static {
     async fn get_policy(&self, r: &Resource, a: &Action) -> Result<Option<Expr>>;
+    /// Store a policy. The expression is rejected if it is not well-formed (see `Expr::validate`)
     async fn set_policy(&self, r: &Resource, a: &Action, c: &Expr) -> Result<()>;
     async fn del_policy(&self, r: &Resource, a: &Action) -> Result<()>;
     async fn policies(&self, r: &Resource) -> Result<Vec<(Action, Expr)>>;
+
+    /// Delete all the policies of a resource and return the number of deleted policies
+    async fn del_policies(&self, r: &Resource) -> Result<usize> {
+        let policies = self.policies(r).await?;
+        for (a, _) in policies.iter() {
+            self.del_policy(r, a).await?;
+        }
+        Ok(policies.len())
+    }
 }
@@ -52,6 +52,10 @@ impl IdentityAttributesReader for BootstrapedIdentityStore {
         l.append(&mut l2);
         Ok(l)
     }
+
+    async fn count(&self) -> Result<usize> {
+        Ok(self.repository.count().await? + self.bootstrapped.count().await?)
+    }
 }
 
 #[async_trait]
@@ -97,6 +101,10 @@ impl IdentitiesReader for BootstrapedIdentityStore {
     async fn get_identity(&self, identifier: &Identifier) -> Result<ChangeHistory> {
         self.repository.get_identity(identifier).await
     }
+
+    async fn get_identities_count(&self) -> Result<usize> {
+        self.repository.get_identities_count().await
+    }
 }
 
 #[async_trait]
@@ -110,6 +118,10 @@ impl IdentitiesWriter for BootstrapedIdentityStore {
             .update_identity(identifier, change_history)
             .await
     }
+
+    async fn delete_identity(&self, identifier: &Identifier) -> Result<()> {
+        self.repository.delete_identity(identifier).await
+    }
 }
 
 impl IdentitiesRepository for BootstrapedIdentityStore {
@@ -1,25 +1,86 @@
-use std::fmt::{Display, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 use time::format_description::well_known::Iso8601;
 use time::OffsetDateTime;
+use tokio::sync::OnceCell;
 
+use ockam::identity::models::{CredentialAndPurposeKey, CredentialData};
 use ockam::identity::storage::LmdbStorage;
-use ockam::identity::{Identifier, IdentitiesRepository, IdentitiesStorage};
+use ockam::identity::utils::now_offset;
+use ockam::identity::{Identifier, IdentitiesRepository, IdentitiesStorage, Identity};
+use ockam_core::env::get_env;
 
 use crate::cli_state::traits::{StateDirTrait, StateItemTrait};
 use crate::cli_state::{CliStateError, DATA_DIR_NAME};
 
 use super::Result;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// Environment variable used to set the path of the identities database
+pub const OCKAM_DATABASE_PATH: &str = "OCKAM_DATABASE_PATH";
+
+/// An identity stored under a name in the local state
+#[derive(Debug, Clone)]
+pub struct NamedIdentity {
+    identity: Identity,
+    name: String,
+    is_default: bool,
+}
+
+impl NamedIdentity {
+    pub fn new(identity: Identity, name: impl Into<String>, is_default: bool) -> Self {
+        Self {
+            identity,
+            name: name.into(),
+            is_default,
+        }
+    }
+
+    /// Return the identity, with its full change history
+    pub fn identity(&self) -> &Identity {
+        &self.identity
+    }
+
+    pub fn identifier(&self) -> &Identifier {
+        self.identity.identifier()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.is_default
+    }
+}
+
+#[derive(Clone)]
 pub struct IdentitiesState {
     dir: PathBuf,
+    /// The identities repository is opened on first use and then shared by all the clones
+    /// of this state, so that the same LMDB environment is not opened several times
+    repository: Arc<OnceCell<Arc<dyn IdentitiesRepository>>>,
+}
+
+impl Debug for IdentitiesState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdentitiesState")
+            .field("dir", &self.dir)
+            .finish()
+    }
+}
+
+impl PartialEq for IdentitiesState {
+    fn eq(&self, other: &Self) -> bool {
+        self.dir == other.dir
+    }
 }
 
+impl Eq for IdentitiesState {}
+
 impl IdentitiesState {
     pub fn get_or_default(&self, name: Option<&str>) -> Result<IdentityState> {
         if let Some(identity_name) = name {
@@ -29,6 +90,18 @@ impl IdentitiesState {
         }
     }
 
+    /// Return the identifier of the identity with the given name.
+    /// Contrary to `get_or_default`, this does not fall back to the default identity
+    /// when no name is provided, so that callers must be explicit about the identity to use
+    pub fn require_identifier_by_name(&self, name: Option<&str>) -> Result<Identifier> {
+        match name {
+            Some(name) => Ok(self.get(name)?.identifier()),
+            None => Err(CliStateError::InvalidOperation(
+                "An identity name must be provided".to_string(),
+            )),
+        }
+    }
+
     pub fn get_by_identifier(&self, identifier: &Identifier) -> Result<IdentityState> {
         self.list()?
             .into_iter()
@@ -39,20 +112,111 @@ impl IdentitiesState {
             })
     }
 
+    /// Return the enrolled identities, ordered by enrollment time and then by name.
+    /// A window of the results can be selected with `offset` and an optional `limit`
+    pub fn list_enrolled_paged(
+        &self,
+        limit: Option<usize>,
+        offset: usize,
+    ) -> Result<Vec<IdentityState>> {
+        let mut enrolled: Vec<(SystemTime, IdentityState)> = self
+            .list()?
+            .into_iter()
+            .filter_map(|i| match &i.config.enrollment_status {
+                Some(status) if status.is_enrolled => Some((status.created_at, i)),
+                _ => None,
+            })
+            .collect();
+        enrolled.sort_by(|(t1, i1), (t2, i2)| t1.cmp(t2).then_with(|| i1.name.cmp(&i2.name)));
+        Ok(enrolled
+            .into_iter()
+            .map(|(_, i)| i)
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    /// Return all the identities, with the default identity first,
+    /// and then the other identities ordered by name
+    pub fn list_named_identities(&self) -> Result<Vec<IdentityState>> {
+        let default_name = self.default().ok().map(|i| i.name().to_string());
+        let mut identities = self.list()?;
+        identities.sort_by(|i1, i2| {
+            let is_default = |i: &IdentityState| Some(i.name()) == default_name.as_deref();
+            is_default(i2)
+                .cmp(&is_default(i1))
+                .then_with(|| i1.name.cmp(&i2.name))
+        });
+        Ok(identities)
+    }
+
+    /// Return the identities matching the given enrollment filter
+    pub fn list_by_enrollment(&self, filter: EnrollmentFilter) -> Result<Vec<IdentityState>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|i| i.matches(filter))
+            .collect())
+    }
+
+    /// Rename an identity. If the identity was the default one, it stays the default one
+    pub fn rename(&self, old_name: &str, new_name: &str) -> Result<IdentityState> {
+        if self.exists(new_name) {
+            return Err(CliStateError::AlreadyExists {
+                resource: Self::default_filename().to_string(),
+                name: new_name.to_string(),
+            });
+        }
+        let identity_state = self.get(old_name)?;
+        let is_default = self.is_default(old_name)?;
+        std::fs::rename(identity_state.path(), self.path(new_name))?;
+        if is_default {
+            self.set_default(new_name)?;
+        }
+        self.get(new_name)
+    }
+
     pub async fn identities_repository(&self) -> Result<Arc<dyn IdentitiesRepository>> {
-        let lmdb_path = self.identities_repository_path()?;
-        Ok(Arc::new(IdentitiesStorage::new(Arc::new(
-            LmdbStorage::new(lmdb_path).await?,
-        ))))
+        let repository = self
+            .repository
+            .get_or_try_init(|| async {
+                let lmdb_path = self.identities_repository_path()?;
+                let storage = LmdbStorage::new(lmdb_path)
+                    .await
+                    .map_err(CliStateError::storage)?;
+                let repository: Arc<dyn IdentitiesRepository> =
+                    Arc::new(IdentitiesStorage::new(Arc::new(storage)));
+                Ok::<_, CliStateError>(repository)
+            })
+            .await?;
+        Ok(repository.clone())
     }
 
+    /// Return the path of the database storing the identities and their attributes.
+    /// It is located in the identities directory unless it is overridden with the
+    /// `OCKAM_DATABASE_PATH` environment variable
     pub fn identities_repository_path(&self) -> Result<PathBuf> {
+        if let Some(path) = Self::database_path_override()? {
+            return Ok(path);
+        }
         let lmdb_path = self
             .dir
             .join(DATA_DIR_NAME)
             .join("authenticated_storage.lmdb");
         Ok(lmdb_path)
     }
+
+    /// Return the database path set with the `OCKAM_DATABASE_PATH` environment variable, if any.
+    /// That path must be absolute
+    pub(crate) fn database_path_override() -> Result<Option<PathBuf>> {
+        match get_env::<PathBuf>(OCKAM_DATABASE_PATH)? {
+            Some(path) if !path.is_absolute() => Err(CliStateError::InvalidPath(format!(
+                "{} (${OCKAM_DATABASE_PATH} must be an absolute path)",
+                path.display()
+            ))),
+            path => Ok(path),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -69,8 +233,45 @@ impl IdentityState {
         self.config.identifier()
     }
 
+    /// Mark the identity as enrolled.
+    /// If the identity is already enrolled, the original enrollment time is kept
     pub fn set_enrollment_status(&mut self) -> Result<()> {
-        self.config.enrollment_status = Some(EnrollmentStatus::enrolled());
+        self.set_enrollment_status_with_expiry(None)
+    }
+
+    /// Mark the identity as enrolled until the given time, if any.
+    /// If the identity is already enrolled, the original enrollment time is kept,
+    /// and an enrollment without expiration is not shortened
+    pub fn set_enrollment_status_with_expiry(
+        &mut self,
+        expires_at: Option<SystemTime>,
+    ) -> Result<()> {
+        let enrollment_status = match &self.config.enrollment_status {
+            Some(status) if status.is_enrolled => EnrollmentStatus {
+                is_enrolled: true,
+                created_at: status.created_at,
+                expires_at: status.expires_at.and(expires_at),
+            },
+            _ => EnrollmentStatus::enrolled(expires_at),
+        };
+        self.config.enrollment_status = Some(enrollment_status);
+        self.persist()
+    }
+
+    /// Mark the identity as enrolled until the expiration of a credential
+    /// issued to this identity
+    pub fn set_enrollment_status_from_credential(
+        &mut self,
+        credential: &CredentialAndPurposeKey,
+    ) -> Result<()> {
+        let versioned_data = credential.credential.get_versioned_data()?;
+        let credential_data = CredentialData::get_data(&versioned_data)?;
+        let expires_at = UNIX_EPOCH + Duration::from_secs(credential_data.expires_at.0);
+        self.set_enrollment_status_with_expiry(Some(expires_at))
+    }
+
+    pub fn unset_enrollment_status(&mut self) -> Result<()> {
+        self.config.enrollment_status = None;
         self.persist()
     }
 
@@ -84,13 +285,32 @@ impl IdentityState {
         &self.name
     }
 
+    /// Return true if the identity is enrolled and its enrollment has not expired
     pub fn is_enrolled(&self) -> bool {
         self.config
             .enrollment_status
             .as_ref()
-            .map(|s| s.is_enrolled)
+            .map(|s| s.is_enrolled && !s.is_expired())
+            .unwrap_or(false)
+    }
+
+    /// Return true if the identity was enrolled but its enrollment has expired
+    pub fn is_enrollment_expired(&self) -> bool {
+        self.config
+            .enrollment_status
+            .as_ref()
+            .map(|s| s.is_enrolled && s.is_expired())
             .unwrap_or(false)
     }
+
+    /// Return true if the identity matches the given enrollment filter
+    pub fn matches(&self, filter: EnrollmentFilter) -> bool {
+        match filter {
+            EnrollmentFilter::Enrolled => self.is_enrolled(),
+            EnrollmentFilter::Expired => self.is_enrollment_expired(),
+            EnrollmentFilter::Any => true,
+        }
+    }
 }
 
 impl Display for IdentityState {
@@ -146,15 +366,36 @@ impl IdentityConfig {
 pub struct EnrollmentStatus {
     pub is_enrolled: bool,
     pub created_at: SystemTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<SystemTime>,
 }
 
 impl EnrollmentStatus {
-    pub fn enrolled() -> EnrollmentStatus {
+    pub fn enrolled(expires_at: Option<SystemTime>) -> EnrollmentStatus {
         EnrollmentStatus {
             is_enrolled: true,
-            created_at: SystemTime::now(),
+            created_at: now_offset().into(),
+            expires_at,
         }
     }
+
+    /// Return true if the enrollment has an expiration time which has passed
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|expires_at| expires_at <= SystemTime::now())
+            .unwrap_or(false)
+    }
+}
+
+/// Filter used to select identities based on their enrollment status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnrollmentFilter {
+    /// Identities with a valid enrollment
+    Enrolled,
+    /// Identities with an enrollment which has expired
+    Expired,
+    /// All identities
+    Any,
 }
 
 impl Display for EnrollmentStatus {
@@ -174,6 +415,17 @@ impl Display for EnrollmentStatus {
             )?,
         }
 
+        if let Some(expires_at) = self.expires_at {
+            match OffsetDateTime::from(expires_at).format(&Iso8601::DEFAULT) {
+                Ok(time_str) => writeln!(f, "Expires at: {}", time_str)?,
+                Err(err) => writeln!(
+                    f,
+                    "Error formatting OffsetDateTime as Iso8601 String: {}",
+                    err
+                )?,
+            }
+        }
+
         Ok(())
     }
 }
@@ -210,6 +462,7 @@ enum IdentityConfigs {
 
 mod traits {
     use ockam_core::async_trait;
+    use tracing::debug;
 
     use crate::cli_state::traits::*;
     use crate::cli_state::{file_stem, CliStateError};
@@ -226,6 +479,7 @@ mod traits {
         fn new(root_path: &Path) -> Self {
             Self {
                 dir: Self::build_dir(root_path),
+                repository: Arc::new(OnceCell::new()),
             }
         }
 
@@ -242,13 +496,25 @@ mod traits {
             };
 
             // If it's the default, remove link
-            if let Ok(default) = self.default() {
-                if default.path == identity.path {
-                    let _ = std::fs::remove_file(self.default_path()?);
-                }
+            let is_default = match self.default() {
+                Ok(default) => default.path == identity.path,
+                Err(_) => false,
+            };
+            if is_default {
+                let _ = std::fs::remove_file(self.default_path()?);
             }
             // Remove identity file
             identity.delete()?;
+
+            // Promote the first remaining identity, by name, as the new default
+            if is_default {
+                let mut names = self.list_items_names()?;
+                names.sort();
+                if let Some(name) = names.first() {
+                    self.set_default(name)?;
+                    debug!(name=%name, "set default identity");
+                }
+            }
             Ok(())
         }
 
@@ -314,6 +580,10 @@ mod traits {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cli_state::CliState;
+    use ockam::identity::identities;
+    use ockam::identity::models::CredentialSchemaIdentifier;
+    use ockam::identity::utils::AttributesBuilder;
 
     #[test]
     fn test_serialize() {
@@ -330,6 +600,310 @@ mod tests {
         assert_eq!(actual, expected)
     }
 
+    #[test]
+    fn test_list_enrolled_paged() {
+        let state = CliState::test().unwrap();
+        let identifiers = [
+            "Ifa804b7fca12a19eed206ae180b5b576860ae651",
+            "Ifa804b7fca12a19eed206ae180b5b576860ae652",
+            "Ifa804b7fca12a19eed206ae180b5b576860ae653",
+            "Ifa804b7fca12a19eed206ae180b5b576860ae654",
+        ];
+        // "bob" and "carol" are enrolled at the same time, "dave" is not enrolled
+        for (name, identifier, enrolled_at) in [
+            ("carol", identifiers[0], Some(1)),
+            ("alice", identifiers[1], Some(2)),
+            ("bob", identifiers[2], Some(1)),
+            ("dave", identifiers[3], None),
+        ] {
+            let config = IdentityConfig {
+                identifier: Identifier::try_from(identifier).unwrap(),
+                enrollment_status: enrolled_at.map(|secs| EnrollmentStatus {
+                    is_enrolled: true,
+                    created_at: SystemTime::from(
+                        OffsetDateTime::from_unix_timestamp(secs).unwrap(),
+                    ),
+                    expires_at: None,
+                }),
+            };
+            state.identities.create(name, config).unwrap();
+        }
+
+        let names = |identities: Vec<IdentityState>| {
+            identities
+                .iter()
+                .map(|i| i.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        let all = state.identities.list_enrolled_paged(None, 0).unwrap();
+        assert_eq!(names(all), vec!["bob", "carol", "alice"]);
+
+        let window = state.identities.list_enrolled_paged(Some(1), 1).unwrap();
+        assert_eq!(names(window), vec!["carol"]);
+
+        let empty = state.identities.list_enrolled_paged(Some(2), 3).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_list_named_identities() {
+        let state = CliState::test().unwrap();
+        for (name, identifier) in [
+            ("carol", "Ifa804b7fca12a19eed206ae180b5b576860ae651"),
+            ("alice", "Ifa804b7fca12a19eed206ae180b5b576860ae652"),
+            ("dave", "Ifa804b7fca12a19eed206ae180b5b576860ae653"),
+            ("bob", "Ifa804b7fca12a19eed206ae180b5b576860ae654"),
+        ] {
+            let config = identity_config(identifier);
+            state.identities.create(name, config).unwrap();
+        }
+        state.identities.set_default("dave").unwrap();
+
+        let names: Vec<String> = state
+            .identities
+            .list_named_identities()
+            .unwrap()
+            .iter()
+            .map(|i| i.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["dave", "alice", "bob", "carol"]);
+    }
+
+    #[test]
+    fn test_list_by_enrollment() {
+        let state = CliState::test().unwrap();
+        let now = SystemTime::now();
+        let hour = std::time::Duration::from_secs(3600);
+        for (name, identifier, expires_at) in [
+            (
+                "expired",
+                "Ifa804b7fca12a19eed206ae180b5b576860ae651",
+                now - hour,
+            ),
+            (
+                "live",
+                "Ifa804b7fca12a19eed206ae180b5b576860ae652",
+                now + hour,
+            ),
+        ] {
+            let config = identity_config(identifier);
+            let mut identity = state.identities.create(name, config).unwrap();
+            identity
+                .set_enrollment_status_with_expiry(Some(expires_at))
+                .unwrap();
+        }
+
+        let expired = state
+            .identities
+            .list_by_enrollment(EnrollmentFilter::Expired)
+            .unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].name(), "expired");
+
+        let enrolled = state
+            .identities
+            .list_by_enrollment(EnrollmentFilter::Enrolled)
+            .unwrap();
+        assert_eq!(enrolled.len(), 1);
+        assert_eq!(enrolled[0].name(), "live");
+
+        let any = state
+            .identities
+            .list_by_enrollment(EnrollmentFilter::Any)
+            .unwrap();
+        assert_eq!(any.len(), 2);
+    }
+
+    #[test]
+    fn test_expired_enrollment() {
+        let state = CliState::test().unwrap();
+        let config = identity_config("Ifa804b7fca12a19eed206ae180b5b576860ae651");
+        let mut identity = state.identities.create("alice", config).unwrap();
+        let hour = std::time::Duration::from_secs(3600);
+
+        // an expired enrollment is not a valid enrollment
+        identity
+            .set_enrollment_status_with_expiry(Some(SystemTime::now() - hour))
+            .unwrap();
+        assert!(!identity.is_enrolled());
+        assert!(identity.is_enrollment_expired());
+
+        // an enrollment without expiration is not shortened by a later expiration
+        identity.unset_enrollment_status().unwrap();
+        identity.set_enrollment_status().unwrap();
+        identity
+            .set_enrollment_status_with_expiry(Some(SystemTime::now() - hour))
+            .unwrap();
+        assert!(identity.is_enrolled());
+        assert!(!identity.is_enrollment_expired());
+    }
+
+    #[tokio::test]
+    async fn test_set_enrollment_status_from_credential() {
+        let identities = identities();
+        let authority = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let subject = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let credential = identities
+            .credentials()
+            .credentials_creation()
+            .issue_credential(
+                authority.identifier(),
+                subject.identifier(),
+                AttributesBuilder::with_schema(CredentialSchemaIdentifier(0)).build(),
+                Duration::from_secs(3600),
+            )
+            .await
+            .unwrap();
+
+        let state = CliState::test().unwrap();
+        let config = IdentityConfig::new(subject.identifier()).await;
+        let mut identity = state.identities.create("subject", config).unwrap();
+        identity
+            .set_enrollment_status_from_credential(&credential)
+            .unwrap();
+
+        let identity = state.identities.get("subject").unwrap();
+        assert!(identity.is_enrolled());
+        let expires_at = identity
+            .config()
+            .enrollment_status
+            .as_ref()
+            .and_then(|s| s.expires_at)
+            .unwrap();
+        assert!(expires_at > SystemTime::now());
+        assert!(expires_at <= SystemTime::now() + Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_require_identifier_by_name() {
+        let state = CliState::test().unwrap();
+        let config = identity_config("Ifa804b7fca12a19eed206ae180b5b576860ae651");
+        let identifier = config.identifier.clone();
+        state.identities.create("alice", config).unwrap();
+
+        // lenient: the default identity is used when no name is given
+        let default = state.identities.get_or_default(None).unwrap();
+        assert_eq!(default.identifier(), identifier);
+
+        // strict: a name must be given
+        assert!(matches!(
+            state.identities.require_identifier_by_name(None),
+            Err(CliStateError::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            state.identities.require_identifier_by_name(Some("bob")),
+            Err(CliStateError::ResourceNotFound { .. })
+        ));
+        assert_eq!(
+            state
+                .identities
+                .require_identifier_by_name(Some("alice"))
+                .unwrap(),
+            identifier
+        );
+    }
+
+    #[test]
+    fn test_rename() {
+        let state = CliState::test().unwrap();
+        let config = identity_config("Ifa804b7fca12a19eed206ae180b5b576860ae651");
+        state.identities.create("alice", config.clone()).unwrap();
+        state.identities.create("bob", config).unwrap();
+
+        // the new name must not be used by another identity
+        let result = state.identities.rename("alice", "bob");
+        assert!(matches!(result, Err(CliStateError::AlreadyExists { .. })));
+
+        let renamed = state.identities.rename("alice", "carol").unwrap();
+        assert_eq!(renamed.name(), "carol");
+        assert!(state.identities.get("carol").is_ok());
+        assert!(matches!(
+            state.identities.get("alice"),
+            Err(CliStateError::ResourceNotFound { .. })
+        ));
+
+        // the renamed identity is still the default one
+        assert!(state.identities.is_default("carol").unwrap());
+    }
+
+    #[test]
+    fn test_set_default_replaces_previous_default() {
+        let state = CliState::test().unwrap();
+        let config = identity_config("Ifa804b7fca12a19eed206ae180b5b576860ae651");
+        state.identities.create("a", config.clone()).unwrap();
+        state.identities.create("b", config).unwrap();
+
+        state.identities.set_default("a").unwrap();
+        assert!(state.identities.is_default("a").unwrap());
+        assert!(!state.identities.is_default("b").unwrap());
+
+        state.identities.set_default("b").unwrap();
+        assert!(!state.identities.is_default("a").unwrap());
+        assert!(state.identities.is_default("b").unwrap());
+        assert_eq!(state.identities.default().unwrap().name(), "b");
+    }
+
+    #[test]
+    fn test_delete_default_promotes_another_identity() {
+        let state = CliState::test().unwrap();
+        let config = identity_config("Ifa804b7fca12a19eed206ae180b5b576860ae651");
+        state.identities.create("c", config.clone()).unwrap();
+        state.identities.create("b", config.clone()).unwrap();
+        state.identities.create("a", config).unwrap();
+        assert!(state.identities.is_default("c").unwrap());
+
+        // the first remaining identity by name becomes the default
+        state.identities.delete("c").unwrap();
+        assert_eq!(state.identities.default().unwrap().name(), "a");
+
+        // deleting a non-default identity keeps the default
+        state.identities.delete("b").unwrap();
+        assert_eq!(state.identities.default().unwrap().name(), "a");
+
+        // there is no default once all the identities are deleted
+        state.identities.delete("a").unwrap();
+        assert!(state.identities.default().is_err());
+        assert!(!state.identities.default_path().unwrap().exists());
+    }
+
+    #[tokio::test]
+    async fn test_identities_repository_is_opened_once() {
+        let state = CliState::test().unwrap();
+        let repository = state.identities.identities_repository().await.unwrap();
+
+        // the same repository is returned on each call, including from a clone of the state
+        for _ in 0..100 {
+            let other = state.identities.identities_repository().await.unwrap();
+            assert!(same_repository(&repository, &other));
+        }
+        let cloned = state.identities.clone();
+        let other = cloned.identities_repository().await.unwrap();
+        assert!(same_repository(&repository, &other));
+    }
+
+    fn same_repository(
+        r1: &Arc<dyn IdentitiesRepository>,
+        r2: &Arc<dyn IdentitiesRepository>,
+    ) -> bool {
+        std::ptr::eq(Arc::as_ptr(r1) as *const (), Arc::as_ptr(r2) as *const ())
+    }
+
+    /// Return the configuration of a non-enrolled identity
+    fn identity_config(identifier: &str) -> IdentityConfig {
+        IdentityConfig {
+            identifier: Identifier::try_from(identifier).unwrap(),
+            enrollment_status: None,
+        }
+    }
+
     fn create_identity_config() -> IdentityConfig {
         let identifier = Identifier::try_from("Ifa804b7fca12a19eed206ae180b5b576860ae651").unwrap();
         IdentityConfig {
@@ -337,6 +911,7 @@ mod tests {
             enrollment_status: Some(EnrollmentStatus {
                 is_enrolled: true,
                 created_at: SystemTime::from(OffsetDateTime::from_unix_timestamp(0).unwrap()),
+                expires_at: None,
             }),
         }
     }
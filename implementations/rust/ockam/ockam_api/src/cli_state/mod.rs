@@ -18,16 +18,20 @@ pub use crate::cli_state::trust_contexts::*;
 use crate::cli_state::user_info::UsersInfoState;
 pub use crate::cli_state::vaults::*;
 use crate::config::cli::LegacyCliConfig;
+use crate::config::lookup::ProjectLookup;
 use miette::Diagnostic;
 use ockam::identity::Identifier;
 use ockam::identity::Identities;
+use ockam::identity::Identity;
 use ockam::identity::Vault;
 use ockam_core::compat::sync::Arc;
-use ockam_core::env::get_env_with_default;
-use ockam_node::Executor;
+use ockam_core::env::get_env;
+use ockam_node::{Executor, StorageLockTimeout};
 use rand::random;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use thiserror::Error;
+use tracing::warn;
 
 type Result<T> = std::result::Result<T, CliStateError>;
 
@@ -43,7 +47,24 @@ pub enum CliStateError {
 
     #[error(transparent)]
     #[diagnostic(code("OCK500"))]
-    Ockam(#[from] ockam_core::Error),
+    Ockam(ockam_core::Error),
+
+    #[error("Unable to access the local storage: {0}")]
+    #[diagnostic(
+        code("OCK507"),
+        help("Please check that the storage files are accessible and not corrupted")
+    )]
+    Storage(ockam_core::Error),
+
+    #[error("Timed out after {timeout:?} waiting for the lock on the storage file {}", .lock_path.display())]
+    #[diagnostic(
+        code("OCK423"),
+        help("Another process may be using the local storage. Please try again, or set a longer timeout, in seconds, with OCKAM_STORAGE_LOCK_TIMEOUT")
+    )]
+    StorageLockTimeout {
+        lock_path: PathBuf,
+        timeout: Duration,
+    },
 
     #[error("A {resource} named {name} already exists")]
     #[diagnostic(
@@ -78,6 +99,39 @@ pub enum CliStateError {
         help("Please try running 'ockam reset' to reset your local configuration")
     )]
     InvalidVersion(String),
+
+    #[error("The stored change history of the identity {name} does not match its identifier {identifier}")]
+    #[diagnostic(
+        code("OCK422"),
+        help("Please try running 'ockam reset' to reset your local configuration")
+    )]
+    IdentityVerificationFailed { name: String, identifier: String },
+}
+
+impl CliStateError {
+    /// Return an error for a failed access to the local storage
+    pub fn storage(e: ockam_core::Error) -> Self {
+        match StorageLockTimeout::find(&e) {
+            Some(t) => Self::lock_timeout(t),
+            None => CliStateError::Storage(e),
+        }
+    }
+
+    fn lock_timeout(t: &StorageLockTimeout) -> Self {
+        CliStateError::StorageLockTimeout {
+            lock_path: t.lock_path.clone(),
+            timeout: t.timeout,
+        }
+    }
+}
+
+impl From<ockam_core::Error> for CliStateError {
+    fn from(e: ockam_core::Error) -> Self {
+        match StorageLockTimeout::find(&e) {
+            Some(t) => Self::lock_timeout(t),
+            None => CliStateError::Ockam(e),
+        }
+    }
 }
 
 impl From<&str> for CliStateError {
@@ -89,7 +143,7 @@ impl From<&str> for CliStateError {
 impl From<CliStateError> for ockam_core::Error {
     fn from(e: CliStateError) -> Self {
         match e {
-            CliStateError::Ockam(e) => e,
+            CliStateError::Ockam(e) | CliStateError::Storage(e) => e,
             _ => ockam_core::Error::new(
                 ockam_core::errcode::Origin::Application,
                 ockam_core::errcode::Kind::Internal,
@@ -99,6 +153,16 @@ impl From<CliStateError> for ockam_core::Error {
     }
 }
 
+/// Options for a [`CliState::reset`]
+/// By default all the state is deleted
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ResetOptions {
+    /// Keep the identities and their default
+    pub keep_identities: bool,
+    /// Keep the vaults and their default
+    pub keep_vaults: bool,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct CliState {
     pub vaults: VaultsState,
@@ -127,7 +191,12 @@ impl CliState {
     /// configuration migration if necessary
     async fn initialize_cli_state() -> Result<CliState> {
         let default = Self::default_dir()?;
-        let dir = default.as_path();
+        Self::initialize_at(default.as_path()).await
+    }
+
+    /// Initialize CliState at the given directory
+    async fn initialize_at(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir.join("defaults"))?;
         let state = Self {
             vaults: VaultsState::init(dir).await?,
             identities: IdentitiesState::init(dir).await?,
@@ -144,9 +213,10 @@ impl CliState {
     }
 
     /// Reset all directories and return a new CliState
-    pub async fn reset(&self) -> Result<CliState> {
-        Self::delete_at(&self.dir)?;
-        Self::initialize_cli_state().await
+    /// The options can be used to preserve the vaults and / or identities
+    pub async fn reset(&self, options: ResetOptions) -> Result<CliState> {
+        Self::delete_at_with_options(&self.dir, options)?;
+        Self::initialize_at(&self.dir).await
     }
 
     pub fn backup_and_reset() -> Result<CliState> {
@@ -155,7 +225,7 @@ impl CliState {
         // Reset backup directory
         let backup_dir = Self::backup_default_dir()?;
         if backup_dir.exists() {
-            let _ = std::fs::remove_dir_all(&backup_dir);
+            std::fs::remove_dir_all(&backup_dir)?;
         }
         std::fs::create_dir_all(&backup_dir)?;
 
@@ -196,33 +266,56 @@ impl CliState {
         Ok(())
     }
 
+    /// Check that the local state can be used:
+    ///  - the legacy configuration file has been migrated
+    ///  - all the stored items can be read and loaded
+    pub fn health_check(&self) -> Result<()> {
+        if self.dir.join("config.json").exists() {
+            return Err(CliStateError::InvalidOperation(
+                "The legacy configuration file has not been migrated".to_string(),
+            ));
+        }
+        self.vaults.check_items()?;
+        self.identities.check_items()?;
+        self.nodes.check_items()?;
+        self.spaces.check_items()?;
+        self.projects.check_items()?;
+        self.credentials.check_items()?;
+        self.trust_contexts.check_items()?;
+        self.users_info.check_items()?;
+        Ok(())
+    }
+
     pub fn delete_at(root_path: &PathBuf) -> Result<()> {
-        // Delete nodes' state and processes, if possible
-        let nodes_state = NodesState::new(root_path);
-        let _ = nodes_state.list().map(|nodes| {
-            nodes.iter().for_each(|n| {
-                let _ = n.delete_sigkill(true);
-            });
-        });
+        Self::delete_at_with_options(root_path, ResetOptions::default())
+    }
 
-        // Delete all other state directories
-        for dir in &[
-            nodes_state.dir(),
-            IdentitiesState::new(root_path).dir(),
-            VaultsState::new(root_path).dir(),
-            SpacesState::new(root_path).dir(),
-            ProjectsState::new(root_path).dir(),
-            CredentialsState::new(root_path).dir(),
-            TrustContextsState::new(root_path).dir(),
-            UsersInfoState::new(root_path).dir(),
-            &root_path.join("defaults"),
-        ] {
-            let _ = std::fs::remove_dir_all(dir);
+    /// Delete the state located at the given path, except for the vaults and / or identities
+    /// if the options specify that they must be kept
+    ///
+    /// All the deletions are attempted, and the paths which could not be deleted are reported
+    /// in the returned error
+    pub fn delete_at_with_options(root_path: &PathBuf, options: ResetOptions) -> Result<()> {
+        let mut errors = vec![];
+
+        // Delete nodes' state and processes
+        let nodes_state = NodesState::new(root_path);
+        match nodes_state.list() {
+            Ok(nodes) => {
+                for node in nodes {
+                    if let Err(e) = node.delete_sigkill(true) {
+                        errors.push(format!("node {}: {e}", node.name()));
+                    }
+                }
+            }
+            Err(e) => errors.push(format!("{}: {e}", nodes_state.dir().display())),
         }
 
-        // Delete config files located at the root of the state directory
-        let config_file = root_path.join("config.json");
-        let _ = std::fs::remove_file(config_file);
+        for path in Self::paths_to_delete_at(root_path, options) {
+            if let Err(e) = Self::delete_path(&path) {
+                errors.push(format!("{}: {e}", path.display()));
+            }
+        }
 
         // If the state directory is now empty, delete it
         let is_empty = std::fs::read_dir(root_path)
@@ -232,7 +325,81 @@ impl CliState {
             let _ = std::fs::remove_dir(root_path);
         }
 
-        Ok(())
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(CliStateError::Io(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("failed to delete {}", errors.join(", ")),
+            )))
+        }
+    }
+
+    /// Return the paths which would be deleted by a reset, without deleting anything
+    pub fn reset_dry_run(&self, options: ResetOptions) -> Vec<PathBuf> {
+        Self::paths_to_delete_at(&self.dir, options)
+    }
+
+    /// Return the existing paths which must be deleted to reset the state located at the given path
+    fn paths_to_delete_at(root_path: &Path, options: ResetOptions) -> Vec<PathBuf> {
+        let mut paths = vec![
+            NodesState::new(root_path).dir().clone(),
+            SpacesState::new(root_path).dir().clone(),
+            ProjectsState::new(root_path).dir().clone(),
+            CredentialsState::new(root_path).dir().clone(),
+            TrustContextsState::new(root_path).dir().clone(),
+            UsersInfoState::new(root_path).dir().clone(),
+        ];
+        if !options.keep_identities {
+            paths.push(IdentitiesState::new(root_path).dir().clone());
+            // The identities database can be located outside of the state directory
+            if let Ok(Some(database_path)) = IdentitiesState::database_path_override() {
+                let mut lock_path = database_path.clone().into_os_string();
+                lock_path.push("-lock");
+                paths.push(database_path);
+                paths.push(lock_path.into());
+            }
+        }
+        if !options.keep_vaults {
+            paths.push(VaultsState::new(root_path).dir().clone());
+        }
+
+        // Delete the default items, unless they refer to some preserved state
+        let defaults_dir = root_path.join("defaults");
+        if options.keep_identities || options.keep_vaults {
+            let mut kept_defaults = vec![];
+            if options.keep_identities {
+                kept_defaults.push(IdentitiesState::default_filename());
+            }
+            if options.keep_vaults {
+                kept_defaults.push(VaultsState::default_filename());
+            }
+            if let Ok(entries) = std::fs::read_dir(&defaults_dir) {
+                for entry in entries.flatten() {
+                    let file_name = entry.file_name();
+                    if !kept_defaults.iter().any(|k| file_name.eq(*k)) {
+                        paths.push(entry.path());
+                    }
+                }
+            }
+        } else {
+            paths.push(defaults_dir);
+        }
+
+        // Delete config files located at the root of the state directory
+        paths.push(root_path.join("config.json"));
+
+        // Defaults are symlinks which might be dangling, so their own metadata is checked
+        paths.retain(|p| p.symlink_metadata().is_ok());
+        paths
+    }
+
+    fn delete_path(path: &Path) -> std::io::Result<()> {
+        if path.symlink_metadata()?.is_dir() {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        }
     }
 
     pub fn delete() -> Result<()> {
@@ -250,17 +417,161 @@ impl CliState {
                 )));
             }
         }
-        identity_state.delete()
+        self.identities.delete(identity_state.name())
+    }
+
+    /// Delete an identity by name. Its change history and attributes are removed from the
+    /// identities repository as well, unless another named identity has the same identifier
+    pub async fn delete_identity_by_name(&self, name: &str) -> Result<()> {
+        let identity_state = self.identities.get(name)?;
+        let identifier = identity_state.identifier();
+        self.delete_identity(identity_state)?;
+
+        let is_shared = self
+            .identities
+            .list()?
+            .iter()
+            .any(|i| i.identifier() == identifier);
+        if !is_shared {
+            let repository = self.identities.identities_repository().await?;
+            repository
+                .as_attributes_writer()
+                .delete(&identifier)
+                .await?;
+            repository
+                .as_identities_writer()
+                .delete_identity(&identifier)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Delete a project and remove its reference from the nodes which were using it.
+    /// The identities of those nodes are unenrolled, unless they are still used by a node
+    /// referencing another project
+    pub fn delete_project(&self, project_name: &str) -> Result<()> {
+        let project = match self.projects.get(project_name) {
+            Ok(project) => project,
+            Err(CliStateError::ResourceNotFound { .. }) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let mut project_identifiers = vec![];
+        let mut other_projects_identifiers = vec![];
+        for node in self.nodes.list()? {
+            let mut setup = node.config().setup_mut();
+            let project_id = setup.project.as_ref().map(|p| p.id.clone());
+            match project_id {
+                Some(id) if id == project.config().id => {
+                    project_identifiers.push(node.config().identifier()?);
+                    setup.project = None;
+                    node.set_setup(&setup)?;
+                }
+                Some(_) => other_projects_identifiers.push(node.config().identifier()?),
+                None => {}
+            }
+        }
+        for identifier in project_identifiers {
+            if !other_projects_identifiers.contains(&identifier) {
+                self.unenroll_identity(&identifier)?;
+            }
+        }
+        self.projects.delete(project_name)
+    }
+
+    pub fn rename_identity(&self, old_name: &str, new_name: &str) -> Result<IdentityState> {
+        let identity_state = self.identities.get(old_name)?;
+        // Abort if identity is being used by some node, since the node refers to the identity file.
+        for node in self.nodes.list()? {
+            if node.config().identity_config()?.identifier() == identity_state.identifier() {
+                return Err(CliStateError::InvalidOperation(format!(
+                    "Can't rename identity '{}' as it's being used by node '{}'",
+                    &identity_state.name(),
+                    &node.name()
+                )));
+            }
+        }
+        self.identities.rename(old_name, new_name)
+    }
+
+    /// Return the path of the database storing the identities and their attributes
+    pub fn database_path(&self) -> Result<PathBuf> {
+        self.identities.identities_repository_path()
     }
 
     /// Returns the default directory for the CLI state.
+    ///
+    /// It is `$OCKAM_HOME` if set, otherwise `$XDG_DATA_HOME/ockam` or `~/.ockam`,
+    /// as selected by `data_dir`.
     pub fn default_dir() -> Result<PathBuf> {
-        Ok(get_env_with_default::<PathBuf>(
-            "OCKAM_HOME",
-            home::home_dir()
-                .ok_or(CliStateError::InvalidPath("$HOME".to_string()))?
-                .join(".ockam"),
-        )?)
+        match get_env::<PathBuf>("OCKAM_HOME")? {
+            Some(ockam_home) => Ok(ockam_home),
+            None => Self::data_dir(),
+        }
+    }
+
+    /// Returns the directory for the CLI state when `$OCKAM_HOME` is not set.
+    ///
+    /// On Linux, `$XDG_DATA_HOME/ockam` is used if it exists or if `~/.ockam` doesn't.
+    /// Otherwise it is `~/.ockam`. If the home directory can't be determined either,
+    /// a directory private to the current user is created in the temporary directory.
+    fn data_dir() -> Result<PathBuf> {
+        let xdg_data_home = if cfg!(target_os = "linux") {
+            get_env::<PathBuf>("XDG_DATA_HOME")?
+        } else {
+            None
+        };
+        Self::resolve_data_dir(xdg_data_home, home::home_dir(), &std::env::temp_dir())
+    }
+
+    fn resolve_data_dir(
+        xdg_data_home: Option<PathBuf>,
+        home_dir: Option<PathBuf>,
+        temp_dir: &Path,
+    ) -> Result<PathBuf> {
+        let xdg_ockam_dir = xdg_data_home
+            .filter(|dir| dir.is_absolute())
+            .map(|dir| dir.join("ockam"));
+        let home_ockam_dir = home_dir.map(|dir| dir.join(".ockam"));
+        if let Some(dir) = xdg_ockam_dir {
+            if dir.exists() || !home_ockam_dir.as_ref().map_or(false, |d| d.exists()) {
+                return Ok(dir);
+            }
+        }
+        match home_ockam_dir {
+            Some(dir) => Ok(dir),
+            None => {
+                let dir = Self::private_temp_dir(temp_dir, nix::unistd::getuid().as_raw())?;
+                warn!(
+                    dir = %dir.display(),
+                    "the home directory can't be determined, using a temporary directory. \
+                     Set $OCKAM_HOME to choose where the CLI state is stored"
+                );
+                Ok(dir)
+            }
+        }
+    }
+
+    /// Returns a directory in the `temp_dir` directory which can only be accessed by the
+    /// user with the given uid. The directory is created if it doesn't exist yet, and an
+    /// error is returned if it exists but is not private to that user.
+    fn private_temp_dir(temp_dir: &Path, uid: u32) -> Result<PathBuf> {
+        use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+        let dir = temp_dir.join(format!("ockam-{uid}"));
+        match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::AlreadyExists => return Err(e.into()),
+            _ => {}
+        }
+        let metadata = std::fs::symlink_metadata(&dir)?;
+        if !metadata.is_dir() || metadata.uid() != uid || metadata.permissions().mode() & 0o077 != 0
+        {
+            return Err(CliStateError::InvalidPath(format!(
+                "{} (it must be a directory only accessible by the current user, \
+                 or $OCKAM_HOME must be set)",
+                dir.display()
+            )));
+        }
+        Ok(dir.join(".ockam"))
     }
 
     /// Returns the default backup directory for the CLI state.
@@ -301,6 +612,36 @@ impl CliState {
         Ok(vault_state)
     }
 
+    /// Return the vault with the given name, creating it with a default configuration
+    /// if it does not exist yet
+    pub async fn create_vault_if_missing(&self, vault_name: &str) -> Result<VaultState> {
+        if self.vaults.exists(vault_name) {
+            self.vaults.get(vault_name)
+        } else {
+            self.vaults
+                .create_async(vault_name, VaultConfig::default())
+                .await
+        }
+    }
+
+    /// Return the AWS KMS vault with the given name, creating it if it does not exist yet.
+    /// An error is returned if a vault with that name exists but is not backed by AWS KMS
+    pub async fn create_kms_vault_state(&self, vault_name: &str) -> Result<VaultState> {
+        if self.vaults.exists(vault_name) {
+            let vault_state = self.vaults.get(vault_name)?;
+            if !vault_state.config().is_aws() {
+                return Err(CliStateError::InvalidOperation(format!(
+                    "The vault '{vault_name}' already exists and is not an AWS KMS vault"
+                )));
+            }
+            Ok(vault_state)
+        } else {
+            self.vaults
+                .create_async(vault_name, VaultConfig::new(true)?)
+                .await
+        }
+    }
+
     pub async fn create_identity_state(
         &self,
         identifier: &Identifier,
@@ -323,6 +664,24 @@ impl CliState {
         self.identities.create(identity_name, identity_config)
     }
 
+    /// Create a new identity with the given name, generating its keys in the vault
+    /// with the given name
+    pub async fn create_identity_with_name_in_vault(
+        &self,
+        name: &str,
+        vault_name: &str,
+    ) -> Result<IdentityState> {
+        let vault = self.vaults.get(vault_name)?.get().await?;
+        let identity = self
+            .get_identities(vault)
+            .await?
+            .identities_creation()
+            .create_identity()
+            .await?;
+        self.create_identity_state(identity.identifier(), Some(name))
+            .await
+    }
+
     pub async fn get_identities(&self, vault: Vault) -> Result<Arc<Identities>> {
         Ok(Identities::builder()
             .with_vault(vault)
@@ -330,6 +689,60 @@ impl CliState {
             .build())
     }
 
+    /// Return the vault used by the node with the given name
+    pub async fn get_node_vault(&self, node_name: &str) -> Result<Vault> {
+        self.nodes.get(node_name)?.config().vault().await
+    }
+
+    /// Return the identifier of the identity used by the node with the given name
+    pub fn get_node_identifier(&self, node_name: &str) -> Result<Identifier> {
+        self.nodes.get(node_name)?.config().identifier()
+    }
+
+    /// Return the project the node with the given name is associated with, if any
+    pub fn get_node_project(&self, node_name: &str) -> Result<Option<ProjectLookup>> {
+        Ok(self.nodes.get(node_name)?.config().setup().project.clone())
+    }
+
+    /// Associate the node with the given name with a project,
+    /// for example after that node has been enrolled into the project
+    pub fn set_node_project(&self, node_name: &str, project: &ProjectLookup) -> Result<()> {
+        let node = self.nodes.get(node_name)?;
+        node.set_setup(node.config().setup_mut().set_project(project.clone()))
+    }
+
+    /// Return the name of the identity used by the node with the given name,
+    /// or `None` if that identity is not stored under a name
+    pub fn get_node_identifier_name(&self, node_name: &str) -> Result<Option<String>> {
+        let identifier = self.get_node_identifier(node_name)?;
+        match self.identities.get_by_identifier(&identifier) {
+            Ok(identity) => Ok(Some(identity.name().to_string())),
+            Err(CliStateError::ResourceNotFound { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Return the nodes which were created as authority nodes
+    pub fn list_authority_nodes(&self) -> Result<Vec<NodeState>> {
+        Ok(self
+            .nodes
+            .list()?
+            .into_iter()
+            .filter(|n| n.config().setup().is_authority_node())
+            .collect())
+    }
+
+    /// Return the nodes using the identity with the given identifier
+    pub fn list_nodes_by_identity(&self, identifier: &Identifier) -> Result<Vec<NodeState>> {
+        let mut nodes = vec![];
+        for node in self.nodes.list()? {
+            if &node.config().identifier()? == identifier {
+                nodes.push(node);
+            }
+        }
+        Ok(nodes)
+    }
+
     pub async fn default_identities(&self) -> Result<Arc<Identities>> {
         Ok(Identities::builder()
             .with_vault(self.vaults.default()?.vault().await?)
@@ -337,12 +750,79 @@ impl CliState {
             .build())
     }
 
-    /// Return true if the user is enrolled.
-    /// At the moment this check only verifies that there is a default project.
-    /// This project should be the project that is created at the end of the enrollment procedure
+    /// Return the identity with the given name after verifying its stored change history.
+    /// An error is returned if the change history is inconsistent with the identifier
+    /// recorded for that identity, for example if the storage has been tampered with
+    pub async fn get_verified_identity_by_name(&self, name: &str) -> Result<Identity> {
+        let identifier = self.identities.get(name)?.identifier();
+        let change_history = self
+            .identities
+            .identities_repository()
+            .await?
+            .get_identity(&identifier)
+            .await?;
+        Identity::import_from_change_history(
+            Some(&identifier),
+            change_history,
+            Vault::create_verifying_vault(),
+        )
+        .await
+        .map_err(|_| CliStateError::IdentityVerificationFailed {
+            name: name.to_string(),
+            identifier: identifier.to_string(),
+        })
+    }
+
+    /// Import an identity from a file containing its change history, and store it under
+    /// the given name. The change history can be either hex-encoded or in its binary format.
+    /// It is verified before being stored
+    pub async fn import_identity_from_file(
+        &self,
+        name: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<IdentityState> {
+        let contents = std::fs::read(path)?;
+        let change_history = std::str::from_utf8(&contents)
+            .ok()
+            .and_then(|s| hex::decode(s.trim()).ok())
+            .unwrap_or(contents);
+        let vault = self.create_vault_state(None).await?.get().await?;
+        let identity = self
+            .get_identities(vault)
+            .await?
+            .identities_creation()
+            .import(None, &change_history)
+            .await?;
+        self.make_identity_state(identity.identifier(), Some(name))
+            .await
+    }
+
+    /// Return the identity with the given name, or the default identity if no name is given,
+    /// together with its name and whether it is the default identity.
+    /// The stored change history of the identity is verified
+    pub async fn get_named_identity(&self, name: Option<&str>) -> Result<NamedIdentity> {
+        let name = self.identities.get_or_default(name)?.name().to_string();
+        let identity = self.get_verified_identity_by_name(&name).await?;
+        let is_default = self.identities.is_default(&name)?;
+        Ok(NamedIdentity::new(identity, name, is_default))
+    }
+
+    /// Remove the enrollment status of the identity with the given identifier
+    pub fn unenroll_identity(&self, identifier: &Identifier) -> Result<()> {
+        let mut identity_state = self.identities.get_by_identifier(identifier)?;
+        identity_state.unset_enrollment_status()
+    }
+
+    /// Return true if the default identity is enrolled.
+    /// This does not check that a space and a project have been provisioned, see `is_fully_provisioned`
     pub fn is_enrolled(&self) -> Result<bool> {
-        let identity_state = self.identities.default()?;
-        if !identity_state.is_enrolled() {
+        Ok(self.identities.default()?.is_enrolled())
+    }
+
+    /// Return true if the default identity is enrolled and there is a default space and a default project.
+    /// This project should be the project that is created at the end of the enrollment procedure
+    pub fn is_fully_provisioned(&self) -> Result<bool> {
+        if !self.is_enrolled()? {
             return Ok(false);
         }
 
@@ -368,25 +848,6 @@ impl CliState {
 
 /// Test support
 impl CliState {
-    #[cfg(test)]
-    /// Initialize CliState at the given directory
-    async fn initialize_at(dir: &Path) -> Result<Self> {
-        std::fs::create_dir_all(dir.join("defaults"))?;
-        let state = Self {
-            vaults: VaultsState::init(dir).await?,
-            identities: IdentitiesState::init(dir).await?,
-            nodes: NodesState::init(dir).await?,
-            spaces: SpacesState::init(dir).await?,
-            projects: ProjectsState::init(dir).await?,
-            credentials: CredentialsState::init(dir).await?,
-            trust_contexts: TrustContextsState::init(dir).await?,
-            users_info: UsersInfoState::init(dir).await?,
-            dir: dir.to_path_buf(),
-        };
-        state.migrate()?;
-        Ok(state)
-    }
-
     /// Create a new CliState (but do not run migrations)
     fn new(dir: &Path) -> Result<Self> {
         std::fs::create_dir_all(dir.join("defaults"))?;
@@ -410,11 +871,7 @@ impl CliState {
 
     /// Return a random root directory
     pub fn test_dir() -> Result<PathBuf> {
-        Ok(home::home_dir()
-            .ok_or(CliStateError::InvalidPath("$HOME".to_string()))?
-            .join(".ockam")
-            .join(".tests")
-            .join(random_name()))
+        Ok(Self::data_dir()?.join(".tests").join(random_name()))
     }
 }
 
@@ -437,10 +894,75 @@ mod tests {
     use crate::cloud::enroll::auth0::UserInfo;
     use crate::config::cli::TrustContextConfig;
     use crate::config::lookup::{ConfigLookup, LookupValue, ProjectLookup, SpaceLookup};
+    use ockam::identity::{
+        IdentitiesReader, IdentitiesWriter, IdentityAttributesReader, IdentityAttributesWriter,
+    };
     use ockam_core::compat::rand::random_string;
     use ockam_multiaddr::MultiAddr;
     use std::str::FromStr;
 
+    #[test]
+    fn test_resolve_data_dir() {
+        let home = tempfile::tempdir().unwrap();
+        let xdg_data_home = tempfile::tempdir().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let resolve = |xdg_data_home: PathBuf| {
+            CliState::resolve_data_dir(
+                Some(xdg_data_home),
+                Some(home.path().to_path_buf()),
+                temp_dir.path(),
+            )
+            .unwrap()
+        };
+
+        // $XDG_DATA_HOME/ockam is used if ~/.ockam doesn't exist
+        let xdg = xdg_data_home.path().to_path_buf();
+        assert_eq!(resolve(xdg.clone()), xdg.join("ockam"));
+
+        // an existing ~/.ockam directory is kept
+        std::fs::create_dir(home.path().join(".ockam")).unwrap();
+        assert_eq!(resolve(xdg.clone()), home.path().join(".ockam"));
+
+        // unless $XDG_DATA_HOME/ockam exists too
+        std::fs::create_dir(xdg.join("ockam")).unwrap();
+        assert_eq!(resolve(xdg.clone()), xdg.join("ockam"));
+
+        // a relative $XDG_DATA_HOME is ignored
+        assert_eq!(
+            resolve(PathBuf::from("relative")),
+            home.path().join(".ockam")
+        );
+    }
+
+    #[test]
+    fn test_resolve_data_dir_without_home() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // without a home directory, a directory private to the user is used
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = CliState::resolve_data_dir(None, None, temp_dir.path()).unwrap();
+        let uid = nix::unistd::getuid().as_raw();
+        let parent = temp_dir.path().join(format!("ockam-{uid}"));
+        assert_eq!(dir, parent.join(".ockam"));
+        let mode = std::fs::metadata(&parent).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // the existing private directory is reused
+        assert_eq!(
+            CliState::private_temp_dir(temp_dir.path(), uid).unwrap(),
+            dir
+        );
+
+        // a directory which is accessible by other users is rejected
+        std::fs::set_permissions(&parent, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(CliState::private_temp_dir(temp_dir.path(), uid).is_err());
+
+        // a directory owned by another user is rejected
+        let other_uid = uid + 1;
+        std::fs::create_dir(temp_dir.path().join(format!("ockam-{other_uid}"))).unwrap();
+        assert!(CliState::private_temp_dir(temp_dir.path(), other_uid).is_err());
+    }
+
     #[tokio::test]
     async fn test_create_default_identity_state() {
         let state = CliState::test().unwrap();
@@ -491,6 +1013,755 @@ mod tests {
         assert_eq!(identity1.path(), identity2.path());
     }
 
+    #[tokio::test]
+    async fn test_unenroll_identity() {
+        let state = CliState::test().unwrap();
+        let identifier = "Ie92f183eb4c324804ef4d62962dea94cf095a265"
+            .try_into()
+            .unwrap();
+        let mut identity = state
+            .create_identity_state(&identifier, None)
+            .await
+            .unwrap();
+        identity.set_enrollment_status().unwrap();
+        assert!(state.identities.default().unwrap().is_enrolled());
+
+        state.unenroll_identity(&identifier).unwrap();
+        assert!(!state.identities.default().unwrap().is_enrolled());
+    }
+
+    #[tokio::test]
+    async fn test_is_enrolled_without_space() {
+        let state = CliState::test().unwrap();
+        let identifier = "Ie92f183eb4c324804ef4d62962dea94cf095a265"
+            .try_into()
+            .unwrap();
+        let mut identity = state
+            .create_identity_state(&identifier, None)
+            .await
+            .unwrap();
+        assert!(!state.is_enrolled().unwrap());
+        assert!(!state.is_fully_provisioned().unwrap());
+
+        identity.set_enrollment_status().unwrap();
+        assert!(state.is_enrolled().unwrap());
+        assert!(state.is_fully_provisioned().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_is_fully_provisioned() {
+        let state = CliState::test().unwrap();
+        let identifier = "Ie92f183eb4c324804ef4d62962dea94cf095a265"
+            .try_into()
+            .unwrap();
+        let mut identity = state
+            .create_identity_state(&identifier, None)
+            .await
+            .unwrap();
+        identity.set_enrollment_status().unwrap();
+        let space = SpaceConfig {
+            name: "s1".to_string(),
+            id: "space_id".to_string(),
+        };
+        state.spaces.create("s1", space).unwrap();
+        state
+            .projects
+            .create("p1", ProjectConfig::default())
+            .unwrap();
+
+        assert!(state.is_enrolled().unwrap());
+        assert!(state.is_fully_provisioned().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_get_verified_identity_by_name() {
+        let state = CliState::test().unwrap();
+        let vault = state.create_vault_state(None).await.unwrap();
+        let identities = state
+            .get_identities(vault.get().await.unwrap())
+            .await
+            .unwrap();
+        let alice = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let bob = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+
+        state
+            .create_identity_state(alice.identifier(), Some("alice"))
+            .await
+            .unwrap();
+        let verified = state.get_verified_identity_by_name("alice").await.unwrap();
+        assert_eq!(verified.identifier(), alice.identifier());
+
+        // store the change history of bob under a different identifier
+        let mallory: Identifier = "Ie92f183eb4c324804ef4d62962dea94cf095a265"
+            .try_into()
+            .unwrap();
+        identities
+            .repository()
+            .update_identity(&mallory, bob.change_history())
+            .await
+            .unwrap();
+        state
+            .create_identity_state(&mallory, Some("mallory"))
+            .await
+            .unwrap();
+        let result = state.get_verified_identity_by_name("mallory").await;
+        assert!(matches!(
+            result,
+            Err(CliStateError::IdentityVerificationFailed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_named_identity() {
+        let state = CliState::test().unwrap();
+        let vault = state.create_vault_state(None).await.unwrap();
+        let alice = state
+            .create_identity_with_name_in_vault("alice", vault.name())
+            .await
+            .unwrap();
+        let bob = state
+            .create_identity_with_name_in_vault("bob", vault.name())
+            .await
+            .unwrap();
+
+        // the first identity is the default one
+        let named = state.get_named_identity(None).await.unwrap();
+        assert_eq!(named.name(), "alice");
+        assert_eq!(named.identifier(), &alice.identifier());
+        assert!(named.is_default());
+
+        let named = state.get_named_identity(Some("bob")).await.unwrap();
+        assert_eq!(named.name(), "bob");
+        assert_eq!(named.identifier(), &bob.identifier());
+        assert!(!named.is_default());
+        let stored = state
+            .identities
+            .identities_repository()
+            .await
+            .unwrap()
+            .get_identity(&bob.identifier())
+            .await
+            .unwrap();
+        assert_eq!(named.identity().change_history(), &stored);
+
+        assert!(state.get_named_identity(Some("carol")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_identity_from_file() {
+        let state = CliState::test().unwrap();
+        let vault = state.create_vault_state(None).await.unwrap();
+        let identity = state
+            .get_identities(vault.get().await.unwrap())
+            .await
+            .unwrap()
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let exported = identity.export().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let hex_path = dir.path().join("identity.hex");
+        std::fs::write(&hex_path, format!("{}\n", hex::encode(&exported))).unwrap();
+        let binary_path = dir.path().join("identity.bin");
+        std::fs::write(&binary_path, &exported).unwrap();
+
+        let from_hex = state
+            .import_identity_from_file("from-hex", &hex_path)
+            .await
+            .unwrap();
+        let from_binary = state
+            .import_identity_from_file("from-binary", &binary_path)
+            .await
+            .unwrap();
+        assert_eq!(&from_hex.identifier(), identity.identifier());
+        assert_eq!(&from_binary.identifier(), identity.identifier());
+
+        // an invalid change history is rejected
+        let invalid_path = dir.path().join("invalid");
+        std::fs::write(&invalid_path, "not an identity").unwrap();
+        assert!(state
+            .import_identity_from_file("invalid", &invalid_path)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_node_identifier() {
+        let state = CliState::test().unwrap();
+        init_node_state(&state, "n1", None, Some("i1"))
+            .await
+            .unwrap();
+
+        let identifier = state.identities.get("i1").unwrap().identifier();
+        assert_eq!(state.get_node_identifier("n1").unwrap(), identifier);
+        assert_eq!(
+            state.get_node_identifier_name("n1").unwrap(),
+            Some("i1".to_string())
+        );
+        assert!(state.get_node_identifier("unknown").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_nodes_by_identity() {
+        let state = CliState::test().unwrap();
+        init_node_state(&state, "n1", None, Some("i1"))
+            .await
+            .unwrap();
+        init_node_state(&state, "n2", None, Some("i2"))
+            .await
+            .unwrap();
+        init_node_state(&state, "n3", None, Some("i1"))
+            .await
+            .unwrap();
+
+        let node_names = |identity_name: &str| {
+            let identifier = state.identities.get(identity_name).unwrap().identifier();
+            let mut names: Vec<String> = state
+                .list_nodes_by_identity(&identifier)
+                .unwrap()
+                .iter()
+                .map(|n| n.name().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(node_names("i1"), vec!["n1", "n3"]);
+        assert_eq!(node_names("i2"), vec!["n2"]);
+    }
+
+    #[tokio::test]
+    async fn test_create_vault_if_missing() {
+        let state = CliState::test().unwrap();
+        let created = state.create_vault_if_missing("v1").await.unwrap();
+        let existing = state.create_vault_if_missing("v1").await.unwrap();
+        assert_eq!(created.name(), existing.name());
+        assert_eq!(state.vaults.list().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_kms_vault_state_rejects_software_vault() {
+        let state = CliState::test().unwrap();
+        state
+            .vaults
+            .create_async("v1", VaultConfig::default())
+            .await
+            .unwrap();
+        let result = state.create_kms_vault_state("v1").await;
+        assert!(matches!(result, Err(CliStateError::InvalidOperation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_delete_identity_by_name() {
+        let state = CliState::test().unwrap();
+        let vault = state.create_vault_state(None).await.unwrap();
+        let identities = state
+            .get_identities(vault.get().await.unwrap())
+            .await
+            .unwrap();
+        let identity = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let identifier = identity.identifier().clone();
+        state
+            .create_identity_state(&identifier, Some("i1"))
+            .await
+            .unwrap();
+        let repository = identities.repository();
+        repository
+            .put_attribute_value(&identifier, b"role".to_vec(), b"admin".to_vec())
+            .await
+            .unwrap();
+
+        state.delete_identity_by_name("i1").await.unwrap();
+        assert!(state.identities.get("i1").is_err());
+        assert!(repository
+            .retrieve_identity(&identifier)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repository
+            .get_attributes(&identifier)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_error_codes() {
+        use miette::Diagnostic;
+
+        let code = |e: CliStateError| e.code().unwrap().to_string();
+        let storage_error = ockam_core::Error::new(
+            ockam_core::errcode::Origin::Application,
+            ockam_core::errcode::Kind::Io,
+            "storage error",
+        );
+        assert_eq!(code(CliStateError::Storage(storage_error)), "OCK507");
+        assert_eq!(
+            code(CliStateError::InvalidVersion("V1".to_string())),
+            "OCK500"
+        );
+        assert_eq!(
+            code(CliStateError::IdentityVerificationFailed {
+                name: "i1".to_string(),
+                identifier: "Ie92f183eb4c324804ef4d62962dea94cf095a265".to_string(),
+            }),
+            "OCK422"
+        );
+        assert_eq!(
+            code(CliStateError::InvalidOperation("invalid".to_string())),
+            "OCK500"
+        );
+
+        // a storage lock timeout has its own code, whichever way the error is converted
+        let lock_timeout = || {
+            ockam_core::Error::new(
+                ockam_core::errcode::Origin::Node,
+                ockam_core::errcode::Kind::Timeout,
+                StorageLockTimeout {
+                    lock_path: PathBuf::from("/ockam/vaults/v1.lock"),
+                    timeout: Duration::from_secs(10),
+                },
+            )
+        };
+        assert_eq!(code(CliStateError::storage(lock_timeout())), "OCK423");
+        assert_eq!(code(lock_timeout().into()), "OCK423");
+        let error = CliStateError::from(lock_timeout()).to_string();
+        assert!(error.contains("/ockam/vaults/v1.lock"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_get_node_vault() {
+        let state = CliState::test().unwrap();
+        state
+            .vaults
+            .create_async("v1", VaultConfig::default())
+            .await
+            .unwrap();
+        state
+            .vaults
+            .create_async("v2", VaultConfig::default())
+            .await
+            .unwrap();
+        init_node_state(&state, "n1", Some("v2"), Some("i1"))
+            .await
+            .unwrap();
+
+        // the node identity key can only be found in the node vault
+        let identifier = state.identities.get("i1").unwrap().identifier();
+        let node_identities = state
+            .get_identities(state.get_node_vault("n1").await.unwrap())
+            .await
+            .unwrap();
+        let identity = node_identities.get_identity(&identifier).await.unwrap();
+        assert!(node_identities
+            .identities_keys()
+            .get_secret_key(&identity)
+            .await
+            .is_ok());
+
+        let default_identities = state.default_identities().await.unwrap();
+        assert!(default_identities
+            .identities_keys()
+            .get_secret_key(&identity)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_init_node_state_with_existing_identity() {
+        let state = CliState::test().unwrap();
+        for vault_name in ["v1", "v2"] {
+            state
+                .vaults
+                .create_async(vault_name, VaultConfig::default())
+                .await
+                .unwrap();
+        }
+        let identity_state = state
+            .create_identity_with_name_in_vault("i1", "v2")
+            .await
+            .unwrap();
+
+        // the node uses the selected identity and vault
+        init_node_state(&state, "n1", Some("v2"), Some("i1"))
+            .await
+            .unwrap();
+        assert_eq!(
+            state.get_node_identifier("n1").unwrap(),
+            identity_state.identifier()
+        );
+        let node_identities = state
+            .get_identities(state.get_node_vault("n1").await.unwrap())
+            .await
+            .unwrap();
+        let identity = node_identities
+            .get_identity(&identity_state.identifier())
+            .await
+            .unwrap();
+        assert!(node_identities
+            .identities_keys()
+            .get_secret_key(&identity)
+            .await
+            .is_ok());
+        assert_eq!(state.identities.list().unwrap().len(), 1);
+
+        // the identity keys must be in the selected vault
+        assert!(init_node_state(&state, "n2", Some("v1"), Some("i1"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_identity_with_name_in_vault() {
+        let state = CliState::test().unwrap();
+        state
+            .vaults
+            .create_async("v1", VaultConfig::default())
+            .await
+            .unwrap();
+        state
+            .vaults
+            .create_async("v2", VaultConfig::default())
+            .await
+            .unwrap();
+        let identity_state = state
+            .create_identity_with_name_in_vault("i1", "v2")
+            .await
+            .unwrap();
+        assert_eq!(identity_state.name(), "i1");
+
+        // the identity key can only be found in the requested vault
+        let identifier = identity_state.identifier();
+        let vault_identities = state
+            .get_identities(state.vaults.get("v2").unwrap().get().await.unwrap())
+            .await
+            .unwrap();
+        let identity = vault_identities.get_identity(&identifier).await.unwrap();
+        assert!(vault_identities
+            .identities_keys()
+            .get_secret_key(&identity)
+            .await
+            .is_ok());
+
+        let default_identities = state.default_identities().await.unwrap();
+        assert!(default_identities
+            .identities_keys()
+            .get_secret_key(&identity)
+            .await
+            .is_err());
+
+        // a missing vault is reported as an error
+        assert!(state
+            .create_identity_with_name_in_vault("i2", "unknown")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_set_node_project() {
+        let state = CliState::test().unwrap();
+        init_node_state(&state, "n1", None, None).await.unwrap();
+        assert_eq!(state.get_node_project("n1").unwrap(), None);
+
+        let project = ProjectLookup {
+            node_route: None,
+            id: "project_id".to_string(),
+            name: "p1".to_string(),
+            identity_id: None,
+            authority: None,
+            okta: None,
+        };
+        state.set_node_project("n1", &project).unwrap();
+        assert_eq!(state.get_node_project("n1").unwrap(), Some(project.clone()));
+
+        assert!(state.set_node_project("unknown", &project).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_authority_nodes() {
+        let state = CliState::test().unwrap();
+        init_node_state(&state, "n1", None, None).await.unwrap();
+        init_node_state(&state, "authority", None, None)
+            .await
+            .unwrap();
+        assert!(state.list_authority_nodes().unwrap().is_empty());
+
+        let node = state.nodes.get("authority").unwrap();
+        node.set_setup(&node.config().setup_mut().set_authority_node())
+            .unwrap();
+
+        let authority_nodes = state.list_authority_nodes().unwrap();
+        assert_eq!(authority_nodes.len(), 1);
+        assert_eq!(authority_nodes[0].name(), "authority");
+        assert_eq!(state.nodes.list().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_project() {
+        let state = CliState::test().unwrap();
+        for (id, name) in [("project_id_1", "p1"), ("project_id_2", "p2")] {
+            let project = ProjectConfig {
+                id: id.to_string(),
+                name: name.to_string(),
+                ..Default::default()
+            };
+            state.projects.create(name, project).unwrap();
+        }
+
+        // i1 is only enrolled through p1, i2 is also enrolled through p2
+        for (node_name, identity_name, project_id) in [
+            ("n1", "i1", "project_id_1"),
+            ("n2", "i2", "project_id_1"),
+            ("n3", "i2", "project_id_2"),
+        ] {
+            init_node_state(&state, node_name, None, Some(identity_name))
+                .await
+                .unwrap();
+            let node = state.nodes.get(node_name).unwrap();
+            node.set_setup(node.config().setup_mut().set_project(ProjectLookup {
+                node_route: None,
+                id: project_id.to_string(),
+                name: "project".to_string(),
+                identity_id: None,
+                authority: None,
+                okta: None,
+            }))
+            .unwrap();
+            let mut identity = state.identities.get(identity_name).unwrap();
+            identity.set_enrollment_status().unwrap();
+        }
+
+        state.delete_project("p1").unwrap();
+        assert!(state.projects.get("p1").is_err());
+        let project_id = |node_name: &str| {
+            state
+                .nodes
+                .get(node_name)
+                .unwrap()
+                .config()
+                .setup()
+                .project
+                .as_ref()
+                .map(|p| p.id.clone())
+        };
+        assert_eq!(project_id("n1"), None);
+        assert_eq!(project_id("n2"), None);
+        assert_eq!(project_id("n3"), Some("project_id_2".to_string()));
+        assert!(!state.identities.get("i1").unwrap().is_enrolled());
+        assert!(state.identities.get("i2").unwrap().is_enrolled());
+
+        // deleting a project which doesn't exist is not an error
+        state.delete_project("p1").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_enroll_identity_keeps_first_enrollment_time() {
+        let state = CliState::test().unwrap();
+        let identifier = "Ie92f183eb4c324804ef4d62962dea94cf095a265"
+            .try_into()
+            .unwrap();
+        let mut identity = state
+            .create_identity_state(&identifier, None)
+            .await
+            .unwrap();
+        identity.set_enrollment_status().unwrap();
+        let first = state
+            .identities
+            .default()
+            .unwrap()
+            .config()
+            .enrollment_status
+            .clone()
+            .unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        let mut identity = state.identities.default().unwrap();
+        identity.set_enrollment_status().unwrap();
+        let second = state
+            .identities
+            .default()
+            .unwrap()
+            .config()
+            .enrollment_status
+            .clone()
+            .unwrap();
+
+        assert_eq!(first.created_at, second.created_at);
+    }
+
+    #[tokio::test]
+    async fn test_reset_keep_identities() {
+        let state = CliState::test().unwrap();
+        let vault_state = state.create_vault_state(None).await.unwrap();
+        let identities = state
+            .get_identities(vault_state.get().await.unwrap())
+            .await
+            .unwrap();
+        let identity = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let identity_state = state
+            .create_identity_state(identity.identifier(), Some("alice"))
+            .await
+            .unwrap();
+        let node_config = NodeConfig::try_from(&state).unwrap();
+        state.nodes.create("node", node_config).unwrap();
+
+        let options = ResetOptions {
+            keep_identities: true,
+            keep_vaults: true,
+        };
+        let state = state.reset(options).await.unwrap();
+
+        // the identity and the vault are still there, as well as their defaults
+        assert_eq!(state.identities.get("alice").unwrap(), identity_state);
+        assert_eq!(state.identities.default().unwrap(), identity_state);
+        assert_eq!(state.vaults.default().unwrap(), vault_state);
+
+        // but the nodes are gone
+        assert!(state.nodes.list().unwrap().is_empty());
+        assert!(state.nodes.default().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reset_keep_identities_only() {
+        let state = CliState::test().unwrap();
+        let vault_state = state.create_vault_state(None).await.unwrap();
+        let identities = state
+            .get_identities(vault_state.get().await.unwrap())
+            .await
+            .unwrap();
+        let identity = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let identity_state = state
+            .create_identity_state(identity.identifier(), Some("alice"))
+            .await
+            .unwrap();
+
+        let options = ResetOptions {
+            keep_identities: true,
+            keep_vaults: false,
+        };
+        let state = state.reset(options).await.unwrap();
+
+        // the identity is still there, as well as its default
+        assert_eq!(state.identities.get("alice").unwrap(), identity_state);
+        assert_eq!(state.identities.default().unwrap(), identity_state);
+
+        // but the vault and its default are gone
+        assert!(state.vaults.list().unwrap().is_empty());
+        assert!(state.vaults.default().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let state = CliState::test().unwrap();
+        state.create_vault_state(None).await.unwrap();
+        let node_config = NodeConfig::try_from(&state).unwrap();
+        state.nodes.create("node", node_config).unwrap();
+        state.health_check().unwrap();
+
+        // a corrupted node configuration is reported
+        let broken = state.nodes.path("broken");
+        std::fs::create_dir_all(&broken).unwrap();
+        std::fs::write(broken.join("setup.json"), "{").unwrap();
+        match state.health_check() {
+            Err(CliStateError::InvalidOperation(msg)) => assert!(msg.contains("'broken'")),
+            other => panic!("unexpected result {other:?}"),
+        }
+        std::fs::remove_dir_all(&broken).unwrap();
+        state.health_check().unwrap();
+
+        // a legacy configuration which was not migrated is reported
+        std::fs::write(state.dir.join("config.json"), "{}").unwrap();
+        assert!(state.health_check().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reset_dry_run() {
+        let state = CliState::test().unwrap();
+        let vault_state = state.create_vault_state(None).await.unwrap();
+        let node_config = NodeConfig::try_from(&state).unwrap();
+        state.nodes.create("node", node_config).unwrap();
+
+        let options = ResetOptions {
+            keep_identities: false,
+            keep_vaults: true,
+        };
+        let paths = state.reset_dry_run(options);
+
+        assert!(paths.contains(state.nodes.dir()));
+        assert!(paths.contains(state.identities.dir()));
+        assert!(!paths.contains(state.vaults.dir()));
+        assert!(paths.contains(
+            &state
+                .dir
+                .join("defaults")
+                .join(NodesState::default_filename())
+        ));
+        assert!(!paths.contains(
+            &state
+                .dir
+                .join("defaults")
+                .join(VaultsState::default_filename())
+        ));
+        // config.json does not exist
+        assert!(!paths.contains(&state.dir.join("config.json")));
+
+        // nothing was deleted
+        assert!(paths.iter().all(|p| p.symlink_metadata().is_ok()));
+        assert!(state.nodes.get("node").is_ok());
+        assert_eq!(state.vaults.default().unwrap(), vault_state);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_delete_at_reports_errors() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let state = CliState::test().unwrap();
+        state.create_vault_state(None).await.unwrap();
+
+        // the spaces directory contents can't be removed
+        let spaces_dir = state.spaces.dir().clone();
+        std::fs::create_dir_all(spaces_dir.join("space")).unwrap();
+        std::fs::set_permissions(&spaces_dir, std::fs::Permissions::from_mode(0o555)).unwrap();
+
+        // permissions are not enforced for the root user
+        if std::fs::write(spaces_dir.join("file"), "").is_ok() {
+            std::fs::set_permissions(&spaces_dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let result = CliState::delete_at(&state.dir);
+        std::fs::set_permissions(&spaces_dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains(&spaces_dir.display().to_string()));
+
+        // the other deletions were still performed
+        assert!(!state.vaults.dir().exists());
+        assert!(!state.dir.join("defaults").exists());
+    }
+
     #[tokio::test]
     async fn migrate_legacy_cli_config() {
         // Before this migration, there was a `config.json` file in the root $OCKAM_HOME directory
@@ -6,7 +6,7 @@ use crate::cli_state::{
 use crate::config::lookup::ProjectLookup;
 use crate::nodes::models::transport::CreateTransportJson;
 use backwards_compatibility::*;
-use miette::{IntoDiagnostic, WrapErr};
+use miette::{miette, IntoDiagnostic, WrapErr};
 use nix::errno::Errno;
 use ockam::identity::Identifier;
 use ockam::identity::Vault;
@@ -104,6 +104,7 @@ impl NodeState {
             })?;
             std::fs::remove_file(self.paths.pid())?;
         }
+        self.clear_ready()?;
         info!(name = %self.name(), "node process killed");
         Ok(())
     }
@@ -132,6 +133,25 @@ impl NodeState {
         Ok(())
     }
 
+    /// Mark the node as ready, once its API listener is bound
+    pub fn set_ready(&self) -> Result<()> {
+        std::fs::write(self.paths.ready(), "")?;
+        Ok(())
+    }
+
+    /// Remove the readiness marker, if any
+    pub fn clear_ready(&self) -> Result<()> {
+        let path = self.paths.ready();
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.paths.ready().exists()
+    }
+
     pub fn is_running(&self) -> bool {
         if let Ok(Some(pid)) = self.pid() {
             let mut sys = System::new();
@@ -158,7 +178,9 @@ impl NodeState {
     }
 
     pub async fn policies_storage(&self) -> Result<LmdbStorage> {
-        Ok(LmdbStorage::new(self.paths.policies_storage()).await?)
+        LmdbStorage::new(self.paths.policies_storage())
+            .await
+            .map_err(CliStateError::storage)
     }
 
     pub fn name(&self) -> &str {
@@ -195,9 +217,12 @@ impl NodeConfig {
         Ok(std::fs::canonicalize(&self.default_vault)?)
     }
 
+    pub fn vault_state(&self) -> Result<VaultState> {
+        VaultState::load(self.vault_path()?)
+    }
+
     pub async fn vault(&self) -> Result<Vault> {
-        let state = VaultState::load(self.vault_path()?)?;
-        state.get().await
+        self.vault_state()?.get().await
     }
 
     pub fn identity_config(&self) -> Result<IdentityConfig> {
@@ -309,6 +334,10 @@ pub struct NodeSetupConfig {
     pub authority_node: Option<bool>,
     pub project: Option<ProjectLookup>,
     pub api_transport: Option<CreateTransportJson>,
+
+    /// Path of the ockam executable which created the node, used to restart it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ockam_exe: Option<PathBuf>,
 }
 
 impl NodeSetupConfig {
@@ -322,6 +351,11 @@ impl NodeSetupConfig {
         self
     }
 
+    /// Return true if the node was created as an authority node
+    pub fn is_authority_node(&self) -> bool {
+        self.authority_node.unwrap_or(false)
+    }
+
     pub fn set_project(&mut self, project: ProjectLookup) -> &mut Self {
         self.project = Some(project);
         self
@@ -332,6 +366,11 @@ impl NodeSetupConfig {
         self
     }
 
+    pub fn set_ockam_exe(mut self, ockam_exe: PathBuf) -> Self {
+        self.ockam_exe = Some(ockam_exe);
+        self
+    }
+
     pub fn api_transport(&self) -> Result<&CreateTransportJson> {
         self.api_transport.as_ref().ok_or_else(|| {
             CliStateError::InvalidOperation(
@@ -369,6 +408,10 @@ impl NodePaths {
         self.path.join("pid")
     }
 
+    fn ready(&self) -> PathBuf {
+        self.path.join("ready")
+    }
+
     fn version(&self) -> PathBuf {
         self.path.join("version")
     }
@@ -492,6 +535,7 @@ mod traits {
                         authority_node: setup.authority_node,
                         project: setup.project,
                         api_transport: None,
+                        ockam_exe: None,
                     };
                     if let Some(t) = setup
                         .transports
@@ -580,20 +624,45 @@ pub async fn init_node_state(
     debug!(name=%node_name, "initializing node state");
     // Get vault specified in the argument, or get the default
     let vault_state = cli_state.create_vault_state(vault_name).await?;
-
-    // create an identity for the node
-    let identity = cli_state
-        .get_identities(vault_state.get().await?)
-        .await?
-        .identities_creation()
-        .create_identity()
-        .await
-        .into_diagnostic()
-        .wrap_err("Failed to create identity")?;
-
-    let identity_state = cli_state
-        .create_identity_state(identity.identifier(), identity_name)
-        .await?;
+    let identities = cli_state.get_identities(vault_state.get().await?).await?;
+
+    // Use the identity specified in the argument, or the default one if it exists.
+    // Otherwise create an identity for the node
+    let identity_state = match cli_state.identities.get_or_default(identity_name) {
+        Ok(identity_state) => {
+            // An explicitly selected vault must contain the identity keys
+            if vault_name.is_some() {
+                let identity = identities
+                    .get_identity(&identity_state.identifier())
+                    .await
+                    .into_diagnostic()?;
+                if identities
+                    .identities_keys()
+                    .get_secret_key(&identity)
+                    .await
+                    .is_err()
+                {
+                    return Err(miette!(
+                        "The keys of the identity {} are not stored in the vault {}",
+                        identity_state.name(),
+                        vault_state.name()
+                    ));
+                }
+            }
+            identity_state
+        }
+        Err(_) => {
+            let identity = identities
+                .identities_creation()
+                .create_identity()
+                .await
+                .into_diagnostic()
+                .wrap_err("Failed to create identity")?;
+            cli_state
+                .create_identity_state(identity.identifier(), identity_name)
+                .await?
+        }
+    };
 
     // Create the node with the given vault and identity
     let node_config = NodeConfigBuilder::default()
@@ -631,8 +700,7 @@ pub async fn add_project_info_to_node_state(
                     CliStateError::InvalidData(format!("Failed to read project: {}", e))
                 })?;
             let proj_config = ProjectConfig::from(&proj_info);
-            let state = cli_state.nodes.get(node_name)?;
-            state.set_setup(state.config().setup_mut().set_project(proj_lookup.clone()))?;
+            cli_state.set_node_project(node_name, &proj_lookup)?;
             cli_state
                 .projects
                 .overwrite(proj_lookup.name, proj_config)?;
@@ -736,4 +804,23 @@ mod tests {
             })
         );
     }
+
+    #[tokio::test]
+    async fn get_missing_node() {
+        let state = CliState::test().unwrap();
+        match state.nodes.get("missing") {
+            Err(CliStateError::ResourceNotFound { resource, name }) => {
+                assert_eq!(resource, "node");
+                assert_eq!(name, "missing");
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+
+        // a node directory without a setup file is not a node
+        std::fs::create_dir_all(state.nodes.path("partial")).unwrap();
+        assert!(matches!(
+            state.nodes.get("partial"),
+            Err(CliStateError::ResourceNotFound { .. })
+        ));
+    }
 }
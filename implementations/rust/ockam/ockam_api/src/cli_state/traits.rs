@@ -130,6 +130,20 @@ pub trait StateDirTrait: Sized + Send + Sync {
         Ok(items)
     }
 
+    /// Check that all the items of this directory can be loaded.
+    /// Contrary to `list`, an item which can't be loaded is reported as an error
+    fn check_items(&self) -> Result<()> {
+        for name in self.list_items_names()? {
+            self.get(&name).map_err(|e| {
+                CliStateError::InvalidOperation(format!(
+                    "The {} '{name}' can't be loaded: {e}",
+                    Self::default_filename()
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
     fn list_items_names(&self) -> Result<Vec<String>> {
         let mut items = Vec::default();
         let iter = std::fs::read_dir(self.dir()).map_err(|e| {
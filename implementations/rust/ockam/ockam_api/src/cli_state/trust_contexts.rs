@@ -1,5 +1,5 @@
 use super::Result;
-use crate::config::cli::TrustContextConfig;
+use crate::config::cli::{CredentialRetrieverConfig, TrustContextConfig};
 use std::fmt::{Display, Formatter};
 use std::path::PathBuf;
 
@@ -24,6 +24,15 @@ impl TrustContextState {
 impl Display for TrustContextState {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Name: {}", self.name)?;
+        writeln!(f, "Id: {}", self.config.id())?;
+        if let Ok(authority) = self.config.authority() {
+            writeln!(f, "Authority identity: {}", authority.identity_str())?;
+            if let Ok(CredentialRetrieverConfig::FromCredentialIssuer(issuer)) =
+                authority.own_credential()
+            {
+                writeln!(f, "Authority route: {}", issuer.multiaddr)?;
+            }
+        }
         Ok(())
     }
 }
@@ -99,3 +108,32 @@ mod traits {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli_state::{CliState, StateDirTrait};
+    use crate::config::cli::{CredentialIssuerConfig, TrustAuthorityConfig};
+    use ockam_multiaddr::MultiAddr;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_display() {
+        let state = CliState::test().unwrap();
+        let route = MultiAddr::from_str("/dnsaddr/localhost/tcp/4000/service/api").unwrap();
+        let authority = TrustAuthorityConfig::new(
+            "0123abcd".to_string(),
+            Some(CredentialRetrieverConfig::FromCredentialIssuer(
+                CredentialIssuerConfig::new("0123abcd".to_string(), route.clone()),
+            )),
+        );
+        let config = TrustContextConfig::new("tc-id".to_string(), Some(authority));
+        let trust_context = state.trust_contexts.create("tc", config).unwrap();
+
+        let output = trust_context.to_string();
+        assert!(output.contains("Name: tc"));
+        assert!(output.contains("Id: tc-id"));
+        assert!(output.contains("Authority identity: 0123abcd"));
+        assert!(output.contains(&format!("Authority route: {route}")));
+    }
+}
@@ -45,7 +45,7 @@ pub struct VaultState {
 
 impl VaultState {
     pub async fn get(&self) -> Result<Vault> {
-        if self.config.aws_kms {
+        if self.config.kind() == VaultKind::AwsKms {
             let mut vault = Vault::create();
             let aws_vault = Arc::new(AwsSigningVault::create().await?);
             vault.identity_vault = aws_vault.clone();
@@ -80,23 +80,39 @@ impl VaultState {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    pub fn kind(&self) -> VaultKind {
+        self.config.kind()
+    }
 }
 
 impl Display for VaultState {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Name: {}", self.name)?;
-        writeln!(
-            f,
-            "Type: {}",
-            match self.config.is_aws() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-        )?;
+        writeln!(f, "Type: {}", self.kind())?;
         Ok(())
     }
 }
 
+/// The kind of storage backing a vault
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultKind {
+    /// Keys are stored in a local file
+    Software,
+    /// Keys are stored in AWS KMS
+    AwsKms,
+}
+
+impl Display for VaultKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VaultKind::Software => f.write_str("OCKAM"),
+            VaultKind::AwsKms => f.write_str("AWS KMS"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Default)]
 pub struct VaultConfig {
     #[serde(default)]
@@ -111,6 +127,14 @@ impl VaultConfig {
     pub fn is_aws(&self) -> bool {
         self.aws_kms
     }
+
+    pub fn kind(&self) -> VaultKind {
+        if self.aws_kms {
+            VaultKind::AwsKms
+        } else {
+            VaultKind::Software
+        }
+    }
 }
 
 mod traits {
@@ -211,3 +235,26 @@ mod traits {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_kind_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, aws_kms, kind) in [
+            ("software", false, VaultKind::Software),
+            ("kms", true, VaultKind::AwsKms),
+        ] {
+            let path = dir.path().join(format!("{name}.json"));
+            let state = VaultState::new(path.clone(), VaultConfig::new(aws_kms).unwrap()).unwrap();
+            assert_eq!(state.kind(), kind);
+            assert_eq!(VaultState::load(path).unwrap().kind(), kind);
+        }
+
+        // vaults stored without the aws_kms flag are software vaults
+        let config: VaultConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.kind(), VaultKind::Software);
+    }
+}
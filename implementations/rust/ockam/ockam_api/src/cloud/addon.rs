@@ -7,6 +7,7 @@ use ockam_core::api::Request;
 use ockam_core::async_trait;
 use ockam_node::Context;
 use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
 
 const TARGET: &str = "ockam_api::cloud::addon";
 const API_SERVICE: &str = "projects";
@@ -23,19 +24,44 @@ pub struct Addon {
     pub enabled: bool,
 }
 
-#[derive(Encode, Decode, Serialize, Deserialize, Debug)]
+#[derive(Encode, Decode, Serialize, Deserialize)]
 #[rustfmt::skip]
 #[cbor(map)]
 pub struct ConfluentConfig {
     #[cbor(n(1))] pub bootstrap_server: String,
+    #[cbor(n(2))] pub api_key: Option<String>,
+    #[cbor(n(3))] pub api_secret: Option<String>,
 }
 
 impl ConfluentConfig {
     pub fn new<S: Into<String>>(bootstrap_server: S) -> Self {
         Self {
             bootstrap_server: bootstrap_server.into(),
+            api_key: None,
+            api_secret: None,
         }
     }
+
+    /// Set the SASL credentials used to authenticate to the Kafka cluster
+    pub fn with_credentials<S: Into<String>>(mut self, api_key: S, api_secret: S) -> Self {
+        self.api_key = Some(api_key.into());
+        self.api_secret = Some(api_secret.into());
+        self
+    }
+}
+
+/// The API secret is not displayed to avoid leaking it in logs
+impl Debug for ConfluentConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfluentConfig")
+            .field("bootstrap_server", &self.bootstrap_server)
+            .field("api_key", &self.api_key)
+            .field(
+                "api_secret",
+                &self.api_secret.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
 }
 
 #[derive(Encode, Decode, Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
@@ -196,3 +222,37 @@ impl Addons for Controller {
             .into_diagnostic()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confluent_config_without_credentials() {
+        let config = ConfluentConfig::new("localhost:9092");
+        let bytes = minicbor::to_vec(&config).unwrap();
+        let decoded: ConfluentConfig = minicbor::decode(&bytes).unwrap();
+        assert_eq!(decoded.bootstrap_server, "localhost:9092");
+        assert_eq!(decoded.api_key, None);
+        assert_eq!(decoded.api_secret, None);
+    }
+
+    #[test]
+    fn confluent_config_with_credentials() {
+        let config = ConfluentConfig::new("localhost:9092").with_credentials("key", "secret");
+        let bytes = minicbor::to_vec(&config).unwrap();
+        let decoded: ConfluentConfig = minicbor::decode(&bytes).unwrap();
+        assert_eq!(decoded.bootstrap_server, "localhost:9092");
+        assert_eq!(decoded.api_key, Some("key".to_string()));
+        assert_eq!(decoded.api_secret, Some("secret".to_string()));
+    }
+
+    #[test]
+    fn confluent_config_debug_hides_the_secret() {
+        let config = ConfluentConfig::new("localhost:9092").with_credentials("key", "secret");
+        let debug = format!("{config:?}");
+        assert!(debug.contains("\"key\""));
+        assert!(!debug.contains("\"secret\""));
+        assert!(debug.contains("<redacted>"));
+    }
+}
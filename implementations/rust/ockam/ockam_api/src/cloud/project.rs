@@ -418,6 +418,22 @@ mod tests {
         assert_eq!(&socket_addr, "node.dnsaddr.com:4000");
     }
 
+    #[test]
+    fn project_is_ready() {
+        let mut project = Project {
+            access_route: "/dnsaddr/node.dnsaddr.com/tcp/4000/service/api".into(),
+            ..Default::default()
+        };
+        assert!(!project.is_ready());
+
+        project.identity = Some(Identifier([1u8; IDENTIFIER_LEN]));
+        project.authority_access_route = Some("/dnsaddr/node.dnsaddr.com/tcp/4001".into());
+        assert!(!project.is_ready());
+
+        project.authority_identity = Some("0102".into());
+        assert!(project.is_ready());
+    }
+
     impl Arbitrary for OktaConfig {
         fn arbitrary(g: &mut Gen) -> Self {
             Self {
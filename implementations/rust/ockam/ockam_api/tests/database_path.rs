@@ -0,0 +1,31 @@
+use ockam_api::cli_state::identities::OCKAM_DATABASE_PATH;
+use ockam_api::cli_state::{CliState, ResetOptions, StateDirTrait};
+
+/// This test is kept in its own test binary since it modifies the process environment
+#[tokio::test]
+async fn database_path_can_be_overridden() {
+    let state = CliState::test().unwrap();
+    let default_path = state.database_path().unwrap();
+    assert!(default_path.starts_with(state.identities.dir()));
+
+    // an absolute path is used as the database path
+    let database_dir = tempfile::tempdir().unwrap();
+    let database_path = database_dir.path().join("identities.lmdb");
+    std::env::set_var(OCKAM_DATABASE_PATH, &database_path);
+    assert_eq!(state.database_path().unwrap(), database_path);
+
+    state.identities.identities_repository().await.unwrap();
+    assert!(database_path.exists());
+    assert!(!default_path.exists());
+
+    // the database is deleted when the state is reset
+    state.reset(ResetOptions::default()).await.unwrap();
+    assert!(!database_path.exists());
+
+    // a relative path is rejected
+    std::env::set_var(OCKAM_DATABASE_PATH, "identities.lmdb");
+    assert!(state.database_path().is_err());
+
+    std::env::remove_var(OCKAM_DATABASE_PATH);
+    assert_eq!(state.database_path().unwrap(), default_path);
+}
@@ -38,6 +38,9 @@ impl TryFrom<&str> for SystemTrayOnUpdatePayload {
     }
 }
 
+/// Request an update of the system tray.
+/// Requests received while an update is being processed are coalesced by the
+/// event listener into a single update, see `AppState::debounce_event`
 pub fn system_tray_on_update<R: Runtime>(app: &AppHandle<R>) {
     app.trigger_global(SYSTEM_TRAY_ON_UPDATE, None);
 }
@@ -38,7 +38,8 @@ pub fn setup<R: Runtime>(app: &mut App<R>) -> Result<(), Box<dyn Error>> {
         let menu_holder = menu_holder.clone();
         app.listen_global(SYSTEM_TRAY_ON_UPDATE, move |event| {
             let app_state = handle.state::<AppState>();
-            let event_tracker = app_state.debounce_event(&handle, SYSTEM_TRAY_ON_UPDATE);
+            let event_tracker =
+                app_state.debounce_event(&handle, SYSTEM_TRAY_ON_UPDATE, event.payload());
             if event_tracker.is_processing() {
                 return;
             }
@@ -1,20 +1,24 @@
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
 use std::sync::RwLock as StdRwLock;
 
 use miette::IntoDiagnostic;
 use tauri::async_runtime::{block_on, spawn, RwLock};
 use tauri::{AppHandle, Manager, Runtime};
+use tokio::sync::Mutex as AsyncMutex;
 use tracing::{error, info, trace, warn};
 
 pub(crate) use crate::app::state::model::ModelState;
 pub(crate) use crate::app::state::repository::{LmdbModelStateRepository, ModelStateRepository};
 use crate::background_node::{BackgroundNodeClient, Cli};
+use crate::invitations::state::Inlet;
 use ockam::Context;
 use ockam::{NodeBuilder, TcpListenerOptions, TcpTransport};
 use ockam_api::cli_state::{
-    add_project_info_to_node_state, init_node_state, CliState, StateDirTrait, StateItemTrait,
+    add_project_info_to_node_state, init_node_state, CliState, ResetOptions, StateDirTrait,
+    StateItemTrait,
 };
 use ockam_api::cloud::enroll::auth0::UserInfo;
 use ockam_api::cloud::Controller;
@@ -51,6 +55,7 @@ pub struct AppState {
     model_state: Arc<RwLock<ModelState>>,
     model_state_repository: Arc<RwLock<Arc<dyn ModelStateRepository>>>,
     event_manager: StdRwLock<EventManager>,
+    relay_creation_lock: StdRwLock<Arc<AsyncMutex<()>>>,
     background_node_client: Arc<RwLock<Arc<dyn BackgroundNodeClient>>>,
 
     #[cfg(debug_assertions)]
@@ -81,11 +86,13 @@ impl AppState {
         spawn(async move { executor.start_router().await });
         let node_manager = Arc::new(create_node_manager(context.clone(), &cli_state));
         let model_state_repository = create_model_state_repository(&cli_state);
+        let relay_creation_lock = Arc::new(AsyncMutex::new(()));
         let model_state = load_model_state(
             model_state_repository.clone(),
             node_manager.clone(),
             context.clone(),
             &cli_state,
+            relay_creation_lock.clone(),
         );
 
         info!("AppState initialized");
@@ -97,6 +104,7 @@ impl AppState {
             model_state: Arc::new(RwLock::new(model_state)),
             model_state_repository: Arc::new(RwLock::new(model_state_repository)),
             event_manager: StdRwLock::new(EventManager::new()),
+            relay_creation_lock: StdRwLock::new(relay_creation_lock),
             background_node_client: Arc::new(RwLock::new(Arc::new(Cli::new()))),
 
             #[cfg(debug_assertions)]
@@ -113,6 +121,12 @@ impl AppState {
             writer.events.clear();
         }
 
+        // a relay creation still running for the previous state must not block the next ones
+        {
+            let mut writer = self.relay_creation_lock.write().unwrap();
+            *writer = Arc::new(AsyncMutex::new(()));
+        }
+
         // recreate the model state repository since the cli state has changed
         {
             let mut writer = self.model_state.write().await;
@@ -135,7 +149,7 @@ impl AppState {
 
     async fn reset_state(&self) -> miette::Result<()> {
         let mut state = self.state.write().await;
-        match state.reset().await {
+        match state.reset(ResetOptions::default()).await {
             Ok(s) => {
                 *state = s;
                 info!("reset the cli state");
@@ -182,14 +196,20 @@ impl AppState {
         node_manager.clone()
     }
 
+    /// Return the lock which makes sure that only one relay creation attempt runs at a time
+    pub fn relay_creation_lock(&self) -> Arc<AsyncMutex<()>> {
+        self.relay_creation_lock.read().unwrap().clone()
+    }
+
     /// Return a client to access the Controller
     pub async fn controller(&self) -> Result<Controller> {
         let node_manager = self.node_manager.read().await;
         Ok(node_manager.create_controller().await?)
     }
 
+    /// Return true if the user is enrolled and has a default space and a default project
     pub async fn is_enrolled(&self) -> Result<bool> {
-        self.state().await.is_enrolled().map_err(|e| {
+        self.state().await.is_fully_provisioned().map_err(|e| {
             warn!(%e, "Failed to check if user is enrolled");
             e.into()
         })
@@ -216,14 +236,63 @@ impl AppState {
         self.user_info().await.map(|u| u.email)
     }
 
-    pub async fn model_mut(&self, f: impl FnOnce(&mut ModelState)) -> Result<()> {
+    /// Add a TCP outlet to the model state and persist it
+    pub async fn add_tcp_outlet(&self, status: OutletStatus) -> Result<()> {
+        let mut model_state = self.model_state.write().await;
+        self.model_state_repository
+            .read()
+            .await
+            .add_tcp_outlet(&status)
+            .await?;
+        model_state.add_tcp_outlet(status);
+        Ok(())
+    }
+
+    /// Remove a TCP outlet from the model state and persist the change
+    pub async fn delete_tcp_outlet(&self, alias: &str) -> Result<()> {
         let mut model_state = self.model_state.write().await;
-        f(&mut model_state);
         self.model_state_repository
             .read()
             .await
-            .store(&model_state)
+            .delete_tcp_outlet(alias)
             .await?;
+        model_state.delete_tcp_outlet(alias);
+        Ok(())
+    }
+
+    /// Add the inlet of an accepted invitation to the model state and persist it
+    pub(crate) async fn add_inlet(&self, invitation_id: &str, inlet: &Inlet) -> Result<()> {
+        let mut model_state = self.model_state.write().await;
+        self.model_state_repository
+            .read()
+            .await
+            .add_inlet(invitation_id, inlet)
+            .await?;
+        model_state.add_inlet(invitation_id, inlet.clone());
+        Ok(())
+    }
+
+    /// Remove the inlet of an accepted invitation from the model state and persist the change
+    pub(crate) async fn delete_inlet(&self, invitation_id: &str) -> Result<()> {
+        let mut model_state = self.model_state.write().await;
+        self.model_state_repository
+            .read()
+            .await
+            .delete_inlet(invitation_id)
+            .await?;
+        model_state.delete_inlet(invitation_id);
+        Ok(())
+    }
+
+    /// Record that an accepted invitation was deleted by the user and persist it
+    pub(crate) async fn add_deleted_invitation(&self, invitation_id: &str) -> Result<()> {
+        let mut model_state = self.model_state.write().await;
+        self.model_state_repository
+            .read()
+            .await
+            .add_deleted_invitation(invitation_id)
+            .await?;
+        model_state.add_deleted_invitation(invitation_id);
         Ok(())
     }
 
@@ -235,15 +304,17 @@ impl AppState {
     /// Returns an EventDebouncer that will prevent the same event from being processed twice concurrently.
     ///
     /// The returned instance must be held in a variable until the event is processed. Once the variable
-    /// is dropped, the event will be marked as "free".
+    /// is dropped, the event will be marked as "free". If other events were received in the meantime,
+    /// the last one of them is then triggered again, so that it is processed exactly once.
     pub fn debounce_event<R: Runtime>(
         &self,
         app: &AppHandle<R>,
         event_name: &str,
+        payload: Option<&str>,
     ) -> EventDebouncer<R> {
         let is_processing = {
             let mut writer = self.event_manager.write().unwrap();
-            writer.is_processing(event_name)
+            writer.is_processing(event_name, payload)
         };
         EventDebouncer {
             app: app.clone(),
@@ -350,11 +421,13 @@ fn load_model_state(
     node_manager: Arc<InMemoryNode>,
     context: Arc<Context>,
     cli_state: &CliState,
+    relay_creation_lock: Arc<AsyncMutex<()>>,
 ) -> ModelState {
     crate::shared_service::relay::load_model_state(
         context.clone(),
         node_manager.clone(),
         cli_state,
+        relay_creation_lock,
     );
     block_on(async {
         match model_state_repository.load().await {
@@ -379,9 +452,12 @@ fn load_model_state(
 
 pub type EventName = String;
 type IsProcessing = AtomicBool;
+type Payload = Option<String>;
 struct Event {
     name: EventName,
     is_processing: IsProcessing,
+    /// Payload of the last event received while the event was being processed
+    pending: StdMutex<Option<Payload>>,
 }
 
 struct EventManager {
@@ -401,17 +477,21 @@ impl EventManager {
         let event = Event {
             name: event_name.to_string(),
             is_processing: AtomicBool::new(true),
+            pending: StdMutex::new(None),
         };
         self.events.push(event);
         trace!(%event_name, "New event registered");
     }
 
-    /// Check if it's being processed
-    fn is_processing(&mut self, event_name: &str) -> bool {
+    /// Check if it's being processed.
+    /// If it is, the payload is kept so that the event can be processed again afterwards
+    fn is_processing(&mut self, event_name: &str, payload: Option<&str>) -> bool {
         match self.events.iter().find(|e| e.name == event_name) {
             Some(e) => {
                 let is_processing = e.is_processing.load(Ordering::SeqCst);
-                if !is_processing {
+                if is_processing {
+                    *e.pending.lock().unwrap() = Some(payload.map(|p| p.to_string()));
+                } else {
                     e.is_processing.store(true, Ordering::SeqCst);
                 }
                 trace!(%event_name, is_processing, "Event status");
@@ -424,14 +504,16 @@ impl EventManager {
         }
     }
 
-    /// Reset an event after it's been dropped
-    fn reset(&self, event_name: &str, processed: bool) {
-        if let Some(e) = self.events.iter().find(|e| e.name == event_name) {
-            if processed {
-                trace!(%event_name, "Event reset");
-                e.is_processing.store(false, Ordering::SeqCst);
-            }
+    /// Reset an event after it's been dropped.
+    /// Return the payload of the last event received while it was processed, if any
+    fn reset(&self, event_name: &str, processed: bool) -> Option<Payload> {
+        let e = self.events.iter().find(|e| e.name == event_name)?;
+        if !processed {
+            return None;
         }
+        trace!(%event_name, "Event reset");
+        e.is_processing.store(false, Ordering::SeqCst);
+        e.pending.lock().unwrap().take()
     }
 }
 
@@ -449,9 +531,15 @@ impl<R: Runtime> EventDebouncer<R> {
 
 impl<R: Runtime> Drop for EventDebouncer<R> {
     fn drop(&mut self) {
-        let state = self.app.state::<AppState>();
-        let reader = state.event_manager.read().unwrap();
-        reader.reset(&self.event_name, !self.is_processing);
+        let pending = {
+            let state = self.app.state::<AppState>();
+            let reader = state.event_manager.read().unwrap();
+            reader.reset(&self.event_name, !self.is_processing)
+        };
+        if let Some(payload) = pending {
+            trace!(event_name = %self.event_name, "Processing the events received in the meantime");
+            self.app.trigger_global(&self.event_name, payload);
+        }
     }
 }
 
@@ -465,19 +553,48 @@ mod tests {
         let event_name = "e1";
 
         // The first call using an unregistered event will register it and return false
-        assert!(!event_manager.is_processing(event_name));
+        assert!(!event_manager.is_processing(event_name, None));
 
         // The second call will return true, as the event has not been marked as processed
-        assert!(event_manager.is_processing(event_name));
+        assert!(event_manager.is_processing(event_name, None));
 
         // Resetting the event marking it as unprocessed will leave the event as processing
-        event_manager.reset(event_name, false);
-        assert!(event_manager.is_processing(event_name));
+        assert!(event_manager.reset(event_name, false).is_none());
+        assert!(event_manager.is_processing(event_name, None));
 
-        // Resetting the event marking it as processed will leave the event as processed
-        event_manager.reset(event_name, true);
+        // Resetting the event marking it as processed will leave the event as processed.
+        // The event received while it was processed must be processed again
+        assert_eq!(event_manager.reset(event_name, true), Some(None));
 
         // The event is now ready to get processed again
-        assert!(!event_manager.is_processing(event_name));
+        assert!(!event_manager.is_processing(event_name, None));
+        assert_eq!(event_manager.reset(event_name, true), None);
+    }
+
+    #[test]
+    fn test_event_manager_coalesces_events_while_processing() {
+        let mut event_manager = EventManager::new();
+        let event_name = "e1";
+        let mut published = vec![];
+
+        // The first event is processed, the next ones are received while it's being processed
+        let mut processed = 0;
+        for i in 0..50 {
+            if !event_manager.is_processing(event_name, Some(&i.to_string())) {
+                processed = i;
+            }
+        }
+
+        // Once an event is processed, the last event received in the meantime is processed,
+        // as if it was triggered again
+        let mut next = Some(Some(processed.to_string()));
+        while let Some(payload) = next {
+            published.push(payload.unwrap());
+            next = event_manager.reset(event_name, true);
+            if let Some(payload) = next.as_ref() {
+                assert!(!event_manager.is_processing(event_name, payload.as_deref()));
+            }
+        }
+        assert_eq!(published, vec!["0", "49"]);
     }
 }
@@ -1,11 +1,25 @@
+use std::collections::{HashMap, HashSet};
+
 use ockam_api::nodes::models::portal::OutletStatus;
 use serde::{Deserialize, Serialize};
 
+use crate::invitations::state::Inlet;
+
 /// The ModelState stores all the data which is not maintained by the NodeManager.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ModelState {
     #[serde(default = "Vec::new")]
     pub(crate) tcp_outlets: Vec<OutletStatus>,
+
+    /// Inlets created for accepted invitations, keyed by invitation id.
+    /// They are persisted to keep the user choices (enabled / disabled, bind address) across restarts
+    #[serde(default)]
+    pub(crate) inlets: HashMap<String, Inlet>,
+
+    /// Ids of the accepted invitations deleted by the user.
+    /// They are persisted so that a refresh of the invitations doesn't bring them back
+    #[serde(default)]
+    pub(crate) deleted_invitations: HashSet<String>,
 }
 
 impl Default for ModelState {
@@ -16,6 +30,10 @@ impl Default for ModelState {
 
 impl ModelState {
     pub fn new(tcp_outlets: Vec<OutletStatus>) -> Self {
-        Self { tcp_outlets }
+        Self {
+            tcp_outlets,
+            inlets: HashMap::new(),
+            deleted_invitations: HashSet::new(),
+        }
     }
 }
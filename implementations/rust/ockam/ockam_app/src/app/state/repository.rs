@@ -1,10 +1,14 @@
 use std::path::Path;
 
 use miette::miette;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use crate::app::state::model::ModelState;
+use crate::invitations::state::Inlet;
 use ockam::identity::storage::Storage;
 use ockam::LmdbStorage;
+use ockam_api::nodes::models::portal::OutletStatus;
 use ockam_core::async_trait;
 
 use crate::Result;
@@ -12,6 +16,15 @@ use crate::Result;
 const MODEL_STATE_ID: &str = "model_state";
 const MODEL_STATE_KEY: &str = "model_state_key";
 
+/// Namespace of the TCP outlets, stored under one key per alias
+const TCP_OUTLETS_NAMESPACE: &str = "model_state_tcp_outlet";
+
+/// Namespace of the inlets of accepted invitations, stored under one key per invitation id
+const INLETS_NAMESPACE: &str = "model_state_inlet";
+
+/// Namespace of the deleted accepted invitations, stored under one key per invitation id
+const DELETED_INVITATIONS_NAMESPACE: &str = "model_state_deleted_invitation";
+
 /// The ModelStateRepository is responsible for storing and loading
 /// ModelState data (user information, shared services etc...)
 /// The state must be stored everytime it is modified (see set_user_info in AppState for example)
@@ -20,6 +33,22 @@ const MODEL_STATE_KEY: &str = "model_state_key";
 pub trait ModelStateRepository: Send + Sync + 'static {
     async fn store(&self, model_state: &ModelState) -> Result<()>;
     async fn load(&self) -> Result<Option<ModelState>>;
+
+    /// Add a TCP outlet to the stored state, replacing any outlet with the same alias
+    async fn add_tcp_outlet(&self, outlet: &OutletStatus) -> Result<()>;
+
+    /// Remove the TCP outlet with the given alias from the stored state
+    async fn delete_tcp_outlet(&self, alias: &str) -> Result<()>;
+
+    /// Add the inlet of an accepted invitation to the stored state,
+    /// replacing any inlet stored for the same invitation
+    async fn add_inlet(&self, invitation_id: &str, inlet: &Inlet) -> Result<()>;
+
+    /// Remove the inlet of an accepted invitation from the stored state
+    async fn delete_inlet(&self, invitation_id: &str) -> Result<()>;
+
+    /// Record that an accepted invitation was deleted by the user
+    async fn add_deleted_invitation(&self, invitation_id: &str) -> Result<()>;
 }
 
 /// This implementation of the ModelStateRepository piggy-backs for now on the LMDB storage
@@ -36,30 +65,260 @@ impl LmdbModelStateRepository {
             storage: LmdbStorage::new(path).await.map_err(|e| miette!(e))?,
         })
     }
+
+    async fn get_value<T: DeserializeOwned>(&self, id: &str, key: &str) -> Result<Option<T>> {
+        match self.storage.get(id, key).await {
+            Err(e) => Err(miette!(e).into()),
+            Ok(None) => Ok(None),
+            Ok(Some(bytes)) => {
+                Ok(serde_json::from_slice(bytes.as_slice()).map_err(|e| miette!(e))?)
+            }
+        }
+    }
+
+    async fn set_value<T: Serialize>(&self, id: &str, key: &str, value: &T) -> Result<()> {
+        self.storage
+            .set(id, key.to_string(), serde_json::to_vec(value)?)
+            .await
+            .map_err(|e| miette!(e))?;
+        Ok(())
+    }
+
+    async fn delete_value(&self, id: &str, key: &str) -> Result<()> {
+        self.storage.del(id, key).await.map_err(|e| miette!(e))?;
+        Ok(())
+    }
+
+    /// Return the ids of the values stored in a namespace
+    async fn ids(&self, namespace: &str) -> Result<Vec<String>> {
+        Ok(self.storage.keys(namespace).await.map_err(|e| miette!(e))?)
+    }
 }
 
-/// The implementation simply serializes / deserializes the ModelState as JSON
+/// The TCP outlets and the inlets are stored under their own keys so that they can be
+/// added and removed without rewriting the whole state. The rest of the ModelState is
+/// serialized as a single JSON value
 #[async_trait]
 impl ModelStateRepository for LmdbModelStateRepository {
     async fn store(&self, model_state: &ModelState) -> Result<()> {
-        self.storage
-            .set(
-                MODEL_STATE_ID,
-                MODEL_STATE_KEY.to_string(),
-                serde_json::to_vec(model_state)?,
-            )
+        for alias in self.ids(TCP_OUTLETS_NAMESPACE).await? {
+            if !model_state
+                .get_tcp_outlets()
+                .iter()
+                .any(|o| o.alias == alias)
+            {
+                self.delete_tcp_outlet(&alias).await?;
+            }
+        }
+        for outlet in model_state.get_tcp_outlets() {
+            self.add_tcp_outlet(outlet).await?;
+        }
+        for invitation_id in self.ids(INLETS_NAMESPACE).await? {
+            if !model_state.get_inlets().contains_key(&invitation_id) {
+                self.delete_inlet(&invitation_id).await?;
+            }
+        }
+        for (invitation_id, inlet) in model_state.get_inlets() {
+            self.add_inlet(invitation_id, inlet).await?;
+        }
+        for invitation_id in model_state.get_deleted_invitations() {
+            self.add_deleted_invitation(invitation_id).await?;
+        }
+
+        let mut other_values = model_state.clone();
+        other_values.tcp_outlets.clear();
+        other_values.inlets.clear();
+        other_values.deleted_invitations.clear();
+        self.set_value(MODEL_STATE_ID, MODEL_STATE_KEY, &other_values)
             .await
-            .map_err(|e| miette!(e))?;
-        Ok(())
     }
 
     async fn load(&self) -> Result<Option<ModelState>> {
-        match self.storage.get(MODEL_STATE_ID, MODEL_STATE_KEY).await {
-            Err(e) => Err(miette!(e).into()),
-            Ok(None) => Ok(None),
-            Ok(Some(bytes)) => {
-                Ok(serde_json::from_slice(bytes.as_slice()).map_err(|e| miette!(e))?)
+        let stored: Option<ModelState> = self.get_value(MODEL_STATE_ID, MODEL_STATE_KEY).await?;
+        let outlet_aliases = self.ids(TCP_OUTLETS_NAMESPACE).await?;
+        let inlet_ids = self.ids(INLETS_NAMESPACE).await?;
+        let deleted_invitation_ids = self.ids(DELETED_INVITATIONS_NAMESPACE).await?;
+        if stored.is_none()
+            && outlet_aliases.is_empty()
+            && inlet_ids.is_empty()
+            && deleted_invitation_ids.is_empty()
+        {
+            return Ok(None);
+        }
+
+        // A previous version stored the outlets and the inlets with the rest of the state
+        let mut model_state = stored.unwrap_or_default();
+        let must_migrate = !model_state.tcp_outlets.is_empty() || !model_state.inlets.is_empty();
+
+        for alias in outlet_aliases {
+            if let Some(outlet) = self.get_value(&alias, TCP_OUTLETS_NAMESPACE).await? {
+                model_state.add_tcp_outlet(outlet);
             }
         }
+        for invitation_id in inlet_ids {
+            if let Some(inlet) = self.get_value(&invitation_id, INLETS_NAMESPACE).await? {
+                model_state.add_inlet(&invitation_id, inlet);
+            }
+        }
+
+        for invitation_id in deleted_invitation_ids {
+            model_state.add_deleted_invitation(&invitation_id);
+        }
+
+        if must_migrate {
+            self.store(&model_state).await?;
+        }
+        Ok(Some(model_state))
+    }
+
+    async fn add_tcp_outlet(&self, outlet: &OutletStatus) -> Result<()> {
+        self.set_value(&outlet.alias, TCP_OUTLETS_NAMESPACE, outlet)
+            .await
+    }
+
+    async fn delete_tcp_outlet(&self, alias: &str) -> Result<()> {
+        self.delete_value(alias, TCP_OUTLETS_NAMESPACE).await
+    }
+
+    async fn add_inlet(&self, invitation_id: &str, inlet: &Inlet) -> Result<()> {
+        self.set_value(invitation_id, INLETS_NAMESPACE, inlet).await
+    }
+
+    async fn delete_inlet(&self, invitation_id: &str) -> Result<()> {
+        self.delete_value(invitation_id, INLETS_NAMESPACE).await
+    }
+
+    async fn add_deleted_invitation(&self, invitation_id: &str) -> Result<()> {
+        self.set_value(invitation_id, DELETED_INVITATIONS_NAMESPACE, &())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::compat::rand::random_string;
+
+    #[tokio::test]
+    async fn store_and_load_without_duplicates() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("{}.lmdb", random_string()));
+        let repository = LmdbModelStateRepository::new(&path).await?;
+
+        // adding the same outlet twice only keeps one outlet
+        let mut model_state = ModelState::default();
+        let outlet = OutletStatus::new("127.0.0.1:5000".parse().unwrap(), "s1".into(), "s1", None);
+        model_state.add_tcp_outlet(outlet.clone());
+        model_state.add_tcp_outlet(outlet);
+        model_state.add_tcp_outlet(OutletStatus::new(
+            "127.0.0.1:6000".parse().unwrap(),
+            "s2".into(),
+            "s2",
+            None,
+        ));
+
+        // storing the same state twice doesn't duplicate the outlets
+        repository.store(&model_state).await?;
+        repository.store(&model_state).await?;
+        let loaded = repository.load().await?.unwrap();
+        let aliases: Vec<&str> = loaded
+            .get_tcp_outlets()
+            .iter()
+            .map(|o| o.alias.as_str())
+            .collect();
+        assert_eq!(aliases, vec!["s1", "s2"]);
+
+        let _ = std::fs::remove_file(path);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn add_and_delete_tcp_outlets() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("{}.lmdb", random_string()));
+        let repository = LmdbModelStateRepository::new(&path).await?;
+        assert!(repository.load().await?.is_none());
+
+        let outlet = OutletStatus::new("127.0.0.1:5000".parse().unwrap(), "s1".into(), "s1", None);
+        repository.add_tcp_outlet(&outlet).await?;
+        let loaded = repository.load().await?.unwrap();
+        assert_eq!(loaded.get_tcp_outlets().len(), 1);
+        assert_eq!(loaded.get_tcp_outlets()[0].alias, "s1");
+
+        repository.delete_tcp_outlet("s1").await?;
+        let loaded = repository.load().await?.unwrap();
+        assert!(loaded.get_tcp_outlets().is_empty());
+
+        let _ = std::fs::remove_file(path);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn add_deleted_invitations() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("{}.lmdb", random_string()));
+        let repository = LmdbModelStateRepository::new(&path).await?;
+        repository.add_deleted_invitation("invitation_id").await?;
+
+        // the deleted invitations are still known when the application restarts
+        drop(repository);
+        let repository = LmdbModelStateRepository::new(&path).await?;
+        let loaded = repository.load().await?.unwrap();
+        assert!(loaded.get_deleted_invitations().contains("invitation_id"));
+
+        let _ = std::fs::remove_file(path);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn migrate_outlets_stored_with_the_model_state() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("{}.lmdb", random_string()));
+        let repository = LmdbModelStateRepository::new(&path).await?;
+
+        // the outlets used to be stored together with the rest of the model state
+        let outlet = OutletStatus::new("127.0.0.1:5000".parse().unwrap(), "s1".into(), "s1", None);
+        let legacy = ModelState::new(vec![outlet]);
+        repository
+            .set_value(MODEL_STATE_ID, MODEL_STATE_KEY, &legacy)
+            .await?;
+        let loaded = repository.load().await?.unwrap();
+        assert_eq!(loaded.get_tcp_outlets().len(), 1);
+
+        // once migrated, the outlet can be deleted individually
+        repository.delete_tcp_outlet("s1").await?;
+        let loaded = repository.load().await?.unwrap();
+        assert!(loaded.get_tcp_outlets().is_empty());
+
+        let _ = std::fs::remove_file(path);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn add_and_delete_inlets() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("{}.lmdb", random_string()));
+        let repository = LmdbModelStateRepository::new(&path).await?;
+
+        let mut inlet = Inlet {
+            node_name: "node".to_string(),
+            alias: "alias".to_string(),
+            socket_addr: "127.0.0.1:5000".parse().unwrap(),
+            enabled: true,
+        };
+        repository.add_inlet("invitation_id", &inlet).await?;
+
+        // the inlet preferences are updated when the inlet is disabled
+        inlet.disable();
+        repository.add_inlet("invitation_id", &inlet).await?;
+
+        // reloading the repository, as done when the application restarts, restores the inlet
+        drop(repository);
+        let repository = LmdbModelStateRepository::new(&path).await?;
+        let loaded = repository.load().await?.unwrap();
+        assert_eq!(loaded.get_inlets().len(), 1);
+        assert_eq!(loaded.get_inlets().get("invitation_id"), Some(&inlet));
+
+        repository.delete_inlet("invitation_id").await?;
+        let loaded = repository.load().await?.unwrap();
+        assert!(loaded.get_inlets().is_empty());
+
+        let _ = std::fs::remove_file(path);
+        Ok(())
     }
 }
@@ -40,6 +40,7 @@ pub async fn enroll_user<R: Runtime>(app: &AppHandle<R>) -> Result<()> {
                 app_state.context(),
                 app_state.state().await,
                 app_state.node_manager().await,
+                app_state.relay_creation_lock(),
             )
             .await;
             system_tray_on_update(app);
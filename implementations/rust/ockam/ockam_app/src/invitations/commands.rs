@@ -1,27 +1,50 @@
 use std::collections::HashMap;
+use std::future::Future;
 use std::net::SocketAddr;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use miette::IntoDiagnostic;
 use tauri::{AppHandle, Manager, Runtime, State};
+use tokio_retry::strategy::{jitter, ExponentialBackoff};
+use tokio_retry::Retry;
 use tracing::{debug, info, trace, warn};
 
 use ockam_api::address::get_free_address;
 use ockam_api::cli_state::{CliState, StateDirTrait};
 use ockam_api::cloud::project::Project;
-use ockam_api::cloud::share::InvitationListKind;
-use ockam_api::cloud::share::{CreateServiceInvitation, InvitationWithAccess, Invitations};
+use ockam_api::cloud::share::{
+    CreateServiceInvitation, InvitationWithAccess, Invitations, ReceivedInvitation, RoleInShare,
+    SentInvitation, ShareScope,
+};
+use ockam_api::cloud::share::{InvitationList, InvitationListKind};
+use ockam_api::identity::EnrollmentTicket;
+use ockam_multiaddr::MultiAddr;
 
 use crate::app::events::system_tray_on_update;
 use crate::app::{AppState, PROJECT_NAME};
 use crate::background_node::BackgroundNodeClient;
-use crate::invitations::state::{Inlet, ReceivedInvitationStatus};
+use crate::error::Error;
+use crate::invitations::state::{Inlet, InvitationState, ReceivedInvitationStatus};
 use crate::projects::commands::{create_enrollment_ticket, SyncAdminProjectsState};
 use crate::shared_service::relay::RELAY_NAME;
 
 use super::{events::REFRESHED_INVITATIONS, state::SyncInvitationsState};
 
+/// Maximum delay between two attempts to fetch the invitations from the controller
+const MAX_REFRESH_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Maximum number of retries to fetch the invitations from the controller
+/// before giving up until the next refresh
+const MAX_REFRESH_RETRIES: usize = 8;
+
+/// Maximum delay between two attempts to send an invitation
+const MAX_SEND_INVITATION_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Maximum number of retries to send an invitation before recording it as failed
+const MAX_SEND_INVITATION_RETRIES: usize = 4;
+
 pub async fn accept_invitation<R: Runtime>(id: String, app: AppHandle<R>) -> Result<(), String> {
     accept_invitation_impl(id, &app)
         .await
@@ -40,17 +63,52 @@ async fn accept_invitation_impl<R: Runtime>(id: String, app: &AppHandle<R>) -> c
     }
 
     let invitation_state: State<'_, SyncInvitationsState> = app.state();
+    let (app_state, invitation_id) = (app_state.inner(), &id);
+    accept_received_invitation(
+        &invitation_state,
+        &id,
+        move || async move {
+            let controller = app_state.controller().await.into_diagnostic()?;
+            controller
+                .accept_invitation(&app_state.context(), invitation_id.clone())
+                .await
+        },
+        || system_tray_on_update(app),
+    )
+    .await
+}
+
+/// Accept a received invitation by calling the controller with `accept`.
+/// The controller is not called if the invitation has expired, or if it is already
+/// being accepted or has been accepted. `on_update` is called when the invitation status changes
+async fn accept_received_invitation<T, F, Fut>(
+    invitation_state: &SyncInvitationsState,
+    id: &str,
+    accept: F,
+    on_update: impl Fn(),
+) -> crate::Result<()>
+where
+    T: std::fmt::Debug,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = miette::Result<T>>,
+{
     // Update the invitation status to Accepting if it's not already being processed.
     // Otherwise, return early.
     {
         let mut writer = invitation_state.write().await;
+        // An expired invitation can't be accepted, there's no need to call the controller
+        if writer.received.check_expired(id)? {
+            info!(?id, "Invitation has expired and can't be accepted");
+            on_update();
+            return Ok(());
+        }
         match writer.received.status.iter_mut().find(|x| x.0 == id) {
             None => {
                 writer
                     .received
                     .status
-                    .push((id.clone(), ReceivedInvitationStatus::Accepting));
-                system_tray_on_update(app);
+                    .push((id.to_string(), ReceivedInvitationStatus::Accepting));
+                on_update();
             }
             Some((i, s)) => {
                 return match s {
@@ -62,22 +120,23 @@ async fn accept_invitation_impl<R: Runtime>(id: String, app: &AppHandle<R>) -> c
                         debug!(?i, "Invitation was already accepted");
                         Ok(())
                     }
+                    ReceivedInvitationStatus::Expired => {
+                        debug!(?i, "Invitation has expired");
+                        Ok(())
+                    }
                 }
             }
         }
     }
 
-    let controller = app_state.controller().await.into_diagnostic()?;
-    let res = controller
-        .accept_invitation(&app_state.context(), id.clone())
-        .await?;
+    let res = accept().await?;
 
     // Update the invitation status to Accepted
     {
         let mut writer = invitation_state.write().await;
         if let Some(x) = writer.received.status.iter_mut().find(|x| x.0 == id) {
             x.1 = ReceivedInvitationStatus::Accepted;
-            system_tray_on_update(app);
+            on_update();
         }
     }
 
@@ -97,20 +156,23 @@ pub async fn create_service_invitation<R: Runtime>(
         ?outlet_socket_addr,
         "creating service invitation"
     );
-    let state: State<'_, SyncAdminProjectsState> = app.state();
-    let projects = state.read().await;
-    let project_id = projects
-        .iter()
-        .find(|p| p.name == *PROJECT_NAME)
-        .map(|p| p.id.to_owned())
-        .ok_or_else(|| "could not find default project".to_string())?;
-    let enrollment_ticket = create_enrollment_ticket(project_id, app.clone())
-        .await
-        .map_err(|e| e.to_string())?;
+    let project_name = {
+        let state: State<'_, SyncAdminProjectsState> = app.state();
+        let projects = state.read().await;
+        projects
+            .iter()
+            .find(|p| p.name == *PROJECT_NAME)
+            .map(|p| p.name.to_owned())
+            .ok_or_else(|| "could not find default project".to_string())?
+    };
+    let app_state: State<'_, AppState> = app.state();
+    let (socket_addr, enrollment_ticket) = create_service_invitation_ticket(
+        app_state.background_node_client().await,
+        &project_name,
+        &outlet_socket_addr,
+    )
+    .await?;
 
-    let socket_addr = SocketAddr::from_str(outlet_socket_addr.as_str())
-        .into_diagnostic()
-        .map_err(|e| format!("Cannot parse the outlet address as a socket address: {e}"))?;
     let invite_args = super::build_args_for_create_service_invitation(
         &app,
         &socket_addr,
@@ -130,43 +192,165 @@ pub async fn create_service_invitation<R: Runtime>(
     Ok(())
 }
 
+/// List the sent invitations, optionally filtered by scope and role
+#[tauri::command]
+pub async fn list_sent_invitations<R: Runtime>(
+    scope: Option<ShareScope>,
+    role: Option<RoleInShare>,
+    app: AppHandle<R>,
+) -> Result<Vec<SentInvitation>, String> {
+    let invitation_state: State<'_, SyncInvitationsState> = app.state();
+    let reader = invitation_state.read().await;
+    Ok(reader
+        .list_sent_invitations(scope, role)
+        .into_iter()
+        .cloned()
+        .collect())
+}
+
+/// List the received invitations, optionally filtered by scope and role
+#[tauri::command]
+pub async fn list_received_invitations<R: Runtime>(
+    scope: Option<ShareScope>,
+    role: Option<RoleInShare>,
+    app: AppHandle<R>,
+) -> Result<Vec<ReceivedInvitation>, String> {
+    let invitation_state: State<'_, SyncInvitationsState> = app.state();
+    let reader = invitation_state.read().await;
+    Ok(reader
+        .list_received_invitations(scope, role)
+        .into_iter()
+        .cloned()
+        .collect())
+}
+
+/// Validate the outlet address, then create the enrollment ticket sent with a service invitation.
+/// No ticket is created if the outlet address is invalid
+async fn create_service_invitation_ticket(
+    background_node_client: Arc<dyn BackgroundNodeClient>,
+    project_name: &str,
+    outlet_socket_addr: &str,
+) -> Result<(SocketAddr, EnrollmentTicket), String> {
+    let socket_addr = parse_outlet_socket_addr(outlet_socket_addr)?;
+    let enrollment_ticket = create_enrollment_ticket(background_node_client, project_name)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok((socket_addr, enrollment_ticket))
+}
+
+fn parse_outlet_socket_addr(outlet_socket_addr: &str) -> Result<SocketAddr, String> {
+    SocketAddr::from_str(outlet_socket_addr)
+        .into_diagnostic()
+        .map_err(|e| format!("Cannot parse the outlet address as a socket address: {e}"))
+}
+
+/// Send the invitation to the controller, retrying on failure.
+/// The outcome is stored in the invitations state: either the sent invitation,
+/// or a failure which can be displayed to the user
 async fn send_invitation<R: Runtime>(
     invite_args: CreateServiceInvitation,
     app: &AppHandle<R>,
 ) -> crate::Result<()> {
     let state: State<'_, AppState> = app.state();
+    let recipient_email = invite_args.recipient_email.clone();
 
-    let controller = state.controller().await.into_diagnostic()?;
-    let CreateServiceInvitation {
-        expires_at,
-        project_id,
-        recipient_email,
-        project_identity,
-        project_route,
-        project_authority_identity,
-        project_authority_route,
-        shared_node_identity,
-        shared_node_route,
-        enrollment_ticket,
-    } = invite_args;
-    let res = controller
-        .create_service_invitation(
-            &state.context(),
-            expires_at,
-            project_id,
-            recipient_email,
-            project_identity,
-            project_route,
-            project_authority_identity,
-            project_authority_route,
-            shared_node_identity,
-            shared_node_route,
-            enrollment_ticket,
-        )
-        .await
-        .map_err(|e| e.to_string())?;
-    debug!(?res, "invitation sent");
-    Ok(())
+    let result = match state.controller().await.into_diagnostic() {
+        Ok(controller) => {
+            let context = state.context();
+            let (controller, context, invite_args) = (&controller, &context, &invite_args);
+            send_invitation_with_retry(send_invitation_retry_strategy(), move || async move {
+                let CreateServiceInvitation {
+                    expires_at,
+                    project_id,
+                    recipient_email,
+                    project_identity,
+                    project_route,
+                    project_authority_identity,
+                    project_authority_route,
+                    shared_node_identity,
+                    shared_node_route,
+                    enrollment_ticket,
+                } = invite_args.clone();
+                controller
+                    .create_service_invitation(
+                        context,
+                        expires_at,
+                        project_id,
+                        recipient_email,
+                        project_identity,
+                        project_route,
+                        project_authority_identity,
+                        project_authority_route,
+                        shared_node_identity,
+                        shared_node_route,
+                        enrollment_ticket,
+                    )
+                    .await
+            })
+            .await
+        }
+        Err(e) => Err(e),
+    };
+
+    let invitation_state: State<'_, SyncInvitationsState> = app.state();
+    let result = {
+        let mut writer = invitation_state.write().await;
+        record_sent_invitation(&mut writer, recipient_email, result)
+    };
+    system_tray_on_update(app);
+    result
+}
+
+/// Store the outcome of sending an invitation in the invitations state
+fn record_sent_invitation(
+    invitation_state: &mut InvitationState,
+    recipient_email: String,
+    result: miette::Result<SentInvitation>,
+) -> crate::Result<()> {
+    match result {
+        Ok(res) => {
+            debug!(?res, "invitation sent");
+            invitation_state.add_sent(res);
+            Ok(())
+        }
+        Err(e) => {
+            warn!(%e, %recipient_email, "Giving up sending the invitation");
+            invitation_state.add_failed(recipient_email, e.to_string());
+            Err(e.into())
+        }
+    }
+}
+
+/// Return the delays used to retry sending an invitation:
+/// an exponential backoff starting at 1 second, with jitter, and capped at `MAX_SEND_INVITATION_RETRY_DELAY`
+fn send_invitation_retry_strategy() -> impl Iterator<Item = Duration> {
+    ExponentialBackoff::from_millis(2)
+        .factor(500)
+        .max_delay(MAX_SEND_INVITATION_RETRY_DELAY)
+        .map(jitter)
+        .take(MAX_SEND_INVITATION_RETRIES)
+}
+
+/// Send an invitation, retrying with the given strategy when the controller call fails
+async fn send_invitation_with_retry<S, F, Fut>(
+    strategy: S,
+    mut send_invitation: F,
+) -> miette::Result<SentInvitation>
+where
+    S: IntoIterator<Item = Duration>,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = miette::Result<SentInvitation>>,
+{
+    Retry::spawn(strategy, || {
+        let result = send_invitation();
+        async move {
+            result.await.map_err(|e| {
+                warn!(%e, "Failed to send the invitation");
+                e
+            })
+        }
+    })
+    .await
 }
 
 pub async fn refresh_invitations<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
@@ -178,8 +362,14 @@ pub async fn refresh_invitations<R: Runtime>(app: AppHandle<R>) -> Result<(), St
             return Ok(());
         }
         let controller = state.controller().await.map_err(|e| e.to_string())?;
-        let invitations = controller
-            .list_invitations(&state.context(), InvitationListKind::All)
+        let context = state.context();
+        let (controller, context) = (&controller, &context);
+        let invitations =
+            list_invitations_with_retry(refresh_retry_strategy(), move || async move {
+                controller
+                    .list_invitations(context, InvitationListKind::All)
+                    .await
+            })
             .await
             .map_err(|e| e.to_string())?;
         debug!("Invitations fetched");
@@ -196,6 +386,39 @@ pub async fn refresh_invitations<R: Runtime>(app: AppHandle<R>) -> Result<(), St
     Ok(())
 }
 
+/// Return the delays used to retry fetching the invitations:
+/// an exponential backoff starting at 1 second, with jitter, and capped at `MAX_REFRESH_RETRY_DELAY`.
+/// A new strategy is created for each refresh, so a successful refresh resets the backoff
+fn refresh_retry_strategy() -> impl Iterator<Item = Duration> {
+    ExponentialBackoff::from_millis(2)
+        .factor(500)
+        .max_delay(MAX_REFRESH_RETRY_DELAY)
+        .map(jitter)
+        .take(MAX_REFRESH_RETRIES)
+}
+
+/// Fetch the invitations, retrying with the given strategy when the controller call fails
+async fn list_invitations_with_retry<S, F, Fut>(
+    strategy: S,
+    mut list_invitations: F,
+) -> miette::Result<InvitationList>
+where
+    S: IntoIterator<Item = Duration>,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = miette::Result<InvitationList>>,
+{
+    Retry::spawn(strategy, || {
+        let result = list_invitations();
+        async move {
+            result.await.map_err(|e| {
+                warn!(%e, "Failed to fetch the invitations");
+                e
+            })
+        }
+    })
+    .await
+}
+
 async fn refresh_inlets<R: Runtime>(app: &AppHandle<R>) -> crate::Result<()> {
     debug!("Refreshing inlets");
     let app_state: State<'_, AppState> = app.state();
@@ -261,15 +484,38 @@ async fn refresh_inlets<R: Runtime>(app: &AppHandle<R>) -> crate::Result<()> {
         }
     }
     for (invitation_id, i) in running_inlets {
+        let inlet = Inlet::new(i)?;
+        // only persist the inlet when it changed, to avoid rewriting the model state on each refresh
+        if invitations_state.accepted.inlets.get(&invitation_id) != Some(&inlet) {
+            app_state.add_inlet(&invitation_id, &inlet).await?;
+        }
         invitations_state
             .accepted
             .inlets
-            .insert(invitation_id, Inlet::new(i)?);
+            .insert(invitation_id, inlet);
     }
     info!("Inlets refreshed");
     Ok(())
 }
 
+/// Restore the inlets persisted in the model state, so that the user choices
+/// for each accepted invitation (enabled flag, bind address) survive a restart.
+/// The accepted invitations deleted by the user are restored as well, so that they stay deleted
+pub(crate) async fn restore_inlets<R: Runtime>(app: &AppHandle<R>) {
+    let app_state: State<'_, AppState> = app.state();
+    let (inlets, deleted) = app_state
+        .model(|m| (m.get_inlets().clone(), m.get_deleted_invitations().clone()))
+        .await;
+    if inlets.is_empty() && deleted.is_empty() {
+        return;
+    }
+    debug!(count = inlets.len(), "Restoring inlets");
+    let invitation_state: State<'_, SyncInvitationsState> = app.state();
+    let mut writer = invitation_state.write().await;
+    writer.accepted.inlets = inlets;
+    writer.accepted.deleted = deleted;
+}
+
 /// Create the tcp-inlet for the accepted invitation
 /// Returns the inlet SocketAddr
 async fn create_inlet(
@@ -323,6 +569,7 @@ pub(crate) async fn disconnect_tcp_inlet<R: Runtime>(
             return Ok(());
         }
         inlet.disable();
+        app_state.add_inlet(invitation_id, inlet).await?;
         background_node_client
             .inlets()
             .delete(&inlet.node_name, &inlet.alias)
@@ -335,6 +582,7 @@ pub(crate) async fn enable_tcp_inlet<R: Runtime>(
     app: AppHandle<R>,
     invitation_id: &str,
 ) -> crate::Result<()> {
+    let app_state: State<'_, AppState> = app.state();
     let invitation_state: State<'_, SyncInvitationsState> = app.state();
     let mut writer = invitation_state.write().await;
     if let Some(inlet) = writer.accepted.inlets.get_mut(invitation_id) {
@@ -343,12 +591,59 @@ pub(crate) async fn enable_tcp_inlet<R: Runtime>(
             return Ok(());
         }
         inlet.enable();
+        app_state.add_inlet(invitation_id, inlet).await?;
         app.trigger_global(super::events::REFRESH_INVITATIONS, None);
         info!(node = %inlet.node_name, alias = %inlet.alias, "Enabled TCP inlet");
     }
     Ok(())
 }
 
+/// Delete an accepted invitation, together with the TCP inlet and the local node created for it
+pub(crate) async fn delete_accepted_invitation<R: Runtime>(
+    app: AppHandle<R>,
+    invitation_id: &str,
+) -> crate::Result<()> {
+    let app_state: State<'_, AppState> = app.state();
+    let background_node_client = app_state.background_node_client().await;
+    let invitation_state: State<'_, SyncInvitationsState> = app.state();
+
+    // the invitation is marked as deleted before releasing the lock,
+    // so that a concurrent refresh doesn't recreate its inlet
+    let inlet = invitation_state
+        .write()
+        .await
+        .accepted
+        .delete(invitation_id);
+    app_state.add_deleted_invitation(invitation_id).await?;
+    app_state.delete_inlet(invitation_id).await?;
+    match inlet {
+        Some(inlet) => delete_inlet_and_node(background_node_client, &inlet).await?,
+        None => debug!(%invitation_id, "No TCP inlet was created for this invitation"),
+    }
+    system_tray_on_update(&app);
+    info!(%invitation_id, "Deleted accepted invitation");
+    Ok(())
+}
+
+/// Delete the TCP inlet and the local node created for an accepted invitation
+async fn delete_inlet_and_node(
+    background_node_client: Arc<dyn BackgroundNodeClient>,
+    inlet: &Inlet,
+) -> crate::Result<()> {
+    if inlet.enabled {
+        background_node_client
+            .inlets()
+            .delete(&inlet.node_name, &inlet.alias)
+            .await?;
+    }
+    background_node_client
+        .nodes()
+        .delete(&inlet.node_name)
+        .await?;
+    debug!(node = %inlet.node_name, alias = %inlet.alias, "Deleted TCP inlet and node");
+    Ok(())
+}
+
 #[derive(Debug)]
 pub(crate) struct InletDataFromInvitation {
     pub enabled: bool,
@@ -360,6 +655,9 @@ pub(crate) struct InletDataFromInvitation {
 }
 
 impl InletDataFromInvitation {
+    /// Create the inlet data for an accepted invitation.
+    /// If an inlet was already created for this invitation, its socket address is reused
+    /// so that the local bind port stays stable across refreshes.
     pub fn new(
         cli_state: &CliState,
         invitation: &InvitationWithAccess,
@@ -384,6 +682,20 @@ impl InletDataFromInvitation {
                 };
 
                 if let Some(project) = enrollment_ticket.project {
+                    let project_id = project.id.clone();
+                    let local_node_name = format!("ockam_app_{project_id}_{service_name}");
+                    let service_route = format!(
+                        "/project/{project_id}/service/{}/secure/api/service/{service_name}",
+                        *RELAY_NAME
+                    );
+                    // The route is built from the invitation data, make sure that it is a valid
+                    // address now rather than failing later, when connecting the inlet
+                    MultiAddr::from_str(&service_route).map_err(|e| {
+                        Error::App(format!(
+                            "Invalid service route {service_route} for the accepted invitation: {e}"
+                        ))
+                    })?;
+
                     // At this point, the project name will be the project id.
                     let project = cli_state
                         .projects
@@ -394,13 +706,6 @@ impl InletDataFromInvitation {
                         "Project name should be the project id"
                     );
 
-                    let project_id = project.id();
-                    let local_node_name = format!("ockam_app_{project_id}_{service_name}");
-                    let service_route = format!(
-                        "/project/{project_id}/service/{}/secure/api/service/{service_name}",
-                        *RELAY_NAME
-                    );
-
                     let inlet = inlets.get(&invitation.invitation.id);
                     let enabled = inlet.map(|i| i.enabled).unwrap_or(true);
                     let socket_addr = inlet.map(|i| i.socket_addr);
@@ -432,12 +737,347 @@ impl InletDataFromInvitation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::background_node::{Inlets, Nodes, Projects};
+    use miette::miette;
     use ockam::identity::OneTimeCode;
-    use ockam_api::cloud::share::{
-        ReceivedInvitation, RoleInShare, ServiceAccessDetails, ShareScope,
-    };
+    use ockam_api::cloud::share::ServiceAccessDetails;
     use ockam_api::config::lookup::ProjectLookup;
-    use ockam_api::identity::EnrollmentTicket;
+    use ockam_api::nodes::models::portal::InletStatus;
+    use ockam_core::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use tauri::async_runtime::RwLock;
+    use tokio_retry::strategy::FixedInterval;
+
+    #[test]
+    fn test_refresh_retry_strategy_is_capped() {
+        let delays: Vec<Duration> = refresh_retry_strategy().collect();
+        assert_eq!(delays.len(), MAX_REFRESH_RETRIES);
+        assert!(delays.iter().all(|d| *d <= MAX_REFRESH_RETRY_DELAY));
+    }
+
+    #[tokio::test]
+    async fn test_list_invitations_with_retry() {
+        // the controller fails twice, then succeeds
+        let calls = AtomicUsize::new(0);
+        let result = list_invitations_with_retry(FixedInterval::from_millis(1).take(5), || {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call < 2 {
+                    Err(miette!("controller unavailable"))
+                } else {
+                    Ok(InvitationList {
+                        sent: None,
+                        received: Some(vec![]),
+                        accepted: None,
+                    })
+                }
+            }
+        })
+        .await;
+        assert!(result.unwrap().received.is_some());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // the controller keeps failing, the number of attempts is bounded by the strategy
+        let calls = AtomicUsize::new(0);
+        let result = list_invitations_with_retry(FixedInterval::from_millis(1).take(2), || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err(miette!("controller unavailable")) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_accept_received_invitation() {
+        let invitation = |id: &str, expires_at: &str| ReceivedInvitation {
+            id: id.to_string(),
+            expires_at: expires_at.to_string(),
+            grant_role: RoleInShare::Admin,
+            owner_email: "owner_email".to_string(),
+            scope: ShareScope::Service,
+            target_id: "target_id".to_string(),
+        };
+        let mut state = InvitationState::default();
+        state.received.invitations = vec![
+            invitation("expired", "2020-09-12T15:07:14.00"),
+            invitation("valid", "2100-09-12T15:07:14.00"),
+        ];
+        let state: SyncInvitationsState = Arc::new(RwLock::new(state));
+        let status = |id: &str| {
+            let state = state.clone();
+            let id = id.to_string();
+            async move {
+                state
+                    .read()
+                    .await
+                    .received
+                    .status
+                    .iter()
+                    .find(|x| x.0 == id)
+                    .map(|x| x.1.clone())
+            }
+        };
+
+        // the controller counts the calls made to accept an invitation
+        let calls = AtomicUsize::new(0);
+        let accept = || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Ok::<_, miette::Report>(()) }
+        };
+
+        // an expired invitation is not sent to the controller
+        accept_received_invitation(&state, "expired", accept, || {})
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert_eq!(
+            status("expired").await,
+            Some(ReceivedInvitationStatus::Expired)
+        );
+
+        // a valid invitation is accepted once
+        accept_received_invitation(&state, "valid", accept, || {})
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            status("valid").await,
+            Some(ReceivedInvitationStatus::Accepted)
+        );
+        accept_received_invitation(&state, "valid", accept, || {})
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_parse_outlet_socket_addr() {
+        assert_eq!(
+            parse_outlet_socket_addr("127.0.0.1:5000").unwrap(),
+            "127.0.0.1:5000".parse().unwrap()
+        );
+
+        // an invalid address is rejected before any enrollment ticket is created
+        let err = parse_outlet_socket_addr("localhost").unwrap_err();
+        assert!(err.starts_with("Cannot parse the outlet address as a socket address"));
+        let err = parse_outlet_socket_addr("127.0.0.1:port").unwrap_err();
+        assert!(err.starts_with("Cannot parse the outlet address as a socket address"));
+    }
+
+    #[tokio::test]
+    async fn test_create_service_invitation_ticket_with_invalid_address() {
+        let client = MockBackgroundNodeClient::default();
+        let result =
+            create_service_invitation_ticket(Arc::new(client.clone()), "project", "localhost")
+                .await;
+        assert!(result
+            .unwrap_err()
+            .starts_with("Cannot parse the outlet address as a socket address"));
+
+        // no enrollment ticket was requested
+        assert!(client.calls().is_empty());
+
+        // with a valid address, the enrollment ticket is requested
+        let result =
+            create_service_invitation_ticket(Arc::new(client.clone()), "project", "127.0.0.1:5000")
+                .await;
+        assert!(result.is_err());
+        assert_eq!(client.calls(), vec!["create ticket project".to_string()]);
+    }
+
+    #[test]
+    fn test_send_invitation_retry_strategy_is_capped() {
+        let delays: Vec<Duration> = send_invitation_retry_strategy().collect();
+        assert_eq!(delays.len(), MAX_SEND_INVITATION_RETRIES);
+        assert!(delays.iter().all(|d| *d <= MAX_SEND_INVITATION_RETRY_DELAY));
+    }
+
+    #[tokio::test]
+    async fn test_send_invitation_with_retry() {
+        let mut invitation_state = InvitationState::default();
+        let recipient_email = "user@example.com".to_string();
+
+        // the controller fails once, then succeeds
+        let calls = AtomicUsize::new(0);
+        let result = send_invitation_with_retry(FixedInterval::from_millis(1).take(3), || {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call == 0 {
+                    return Err(miette!("controller unavailable"));
+                }
+                let invitation = SentInvitation {
+                    id: "invitation_id".to_string(),
+                    expires_at: "2100-09-12T15:07:14.00".to_string(),
+                    grant_role: RoleInShare::Admin,
+                    owner_id: 0,
+                    recipient_email: "user@example.com".to_string(),
+                    remaining_uses: 1,
+                    scope: ShareScope::Service,
+                    target_id: "target_id".to_string(),
+                };
+                Ok(invitation)
+            }
+        })
+        .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        record_sent_invitation(&mut invitation_state, recipient_email.clone(), result).unwrap();
+        let sent: Vec<&str> = invitation_state
+            .sent
+            .iter()
+            .map(|i| i.id.as_str())
+            .collect();
+        assert_eq!(sent, vec!["invitation_id"]);
+        assert!(invitation_state.failed.is_empty());
+
+        // the controller keeps failing, the failure is returned once the retries are exhausted
+        let calls = AtomicUsize::new(0);
+        let result = send_invitation_with_retry(FixedInterval::from_millis(1).take(2), || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err(miette!("controller unavailable")) }
+        })
+        .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(
+            record_sent_invitation(&mut invitation_state, recipient_email.clone(), result).is_err()
+        );
+        assert_eq!(invitation_state.failed.len(), 1);
+        assert_eq!(invitation_state.failed[0].recipient_email, recipient_email);
+        assert_eq!(invitation_state.sent.len(), 1);
+    }
+
+    /// This background node client records all the calls made to it.
+    /// Only the creation and deletion of nodes and inlets succeed
+    #[derive(Clone, Default)]
+    struct MockBackgroundNodeClient {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockBackgroundNodeClient {
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl BackgroundNodeClient for MockBackgroundNodeClient {
+        fn nodes(&self) -> Box<dyn Nodes> {
+            Box::new(self.clone())
+        }
+
+        fn inlets(&self) -> Box<dyn Inlets> {
+            Box::new(self.clone())
+        }
+
+        fn projects(&self) -> Box<dyn Projects> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[async_trait]
+    impl Nodes for MockBackgroundNodeClient {
+        async fn create(&mut self, node_name: &str) -> crate::Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("create node {node_name}"));
+            Ok(())
+        }
+
+        async fn delete(&mut self, node_name: &str) -> crate::Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("delete node {node_name}"));
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Inlets for MockBackgroundNodeClient {
+        async fn create(
+            &mut self,
+            node_name: &str,
+            from: &SocketAddr,
+            _service_route: &str,
+            _service_name: &str,
+        ) -> crate::Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("create inlet {node_name} {from}"));
+            Ok(())
+        }
+
+        async fn show(&self, node_name: &str, inlet_name: &str) -> crate::Result<InletStatus> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("show inlet {node_name} {inlet_name}"));
+            Err(miette!("not supported by the mock").into())
+        }
+
+        async fn delete(&mut self, node_name: &str, alias: &str) -> crate::Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("delete inlet {node_name} {alias}"));
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Projects for MockBackgroundNodeClient {
+        async fn enroll(&self, node_name: &str, _hex_encoded_ticket: &str) -> crate::Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("enroll node {node_name}"));
+            Err(miette!("not supported by the mock").into())
+        }
+
+        async fn ticket(&self, project_name: &str) -> crate::Result<String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("create ticket {project_name}"));
+            Err(miette!("not supported by the mock").into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_inlet_and_node() {
+        let client = MockBackgroundNodeClient::default();
+        let mut inlet = Inlet {
+            node_name: "local_node_name".to_string(),
+            alias: "alias".to_string(),
+            socket_addr: "127.0.0.1:1000".parse().unwrap(),
+            enabled: true,
+        };
+
+        // the inlet and the node are deleted
+        delete_inlet_and_node(Arc::new(client.clone()), &inlet)
+            .await
+            .unwrap();
+        assert_eq!(
+            client.calls(),
+            vec![
+                "delete inlet local_node_name alias".to_string(),
+                "delete node local_node_name".to_string()
+            ]
+        );
+
+        // a disabled inlet was already deleted, only the node is deleted
+        let client = MockBackgroundNodeClient::default();
+        inlet.disable();
+        delete_inlet_and_node(Arc::new(client.clone()), &inlet)
+            .await
+            .unwrap();
+        assert_eq!(
+            client.calls(),
+            vec!["delete node local_node_name".to_string()]
+        );
+    }
 
     #[test]
     fn test_inlet_data_from_invitation() {
@@ -510,6 +1150,67 @@ mod tests {
         let inlet_data = InletDataFromInvitation::new(&cli_state, &invitation, &inlets)
             .unwrap()
             .unwrap();
-        assert!(inlet_data.socket_addr.is_some());
+        assert_eq!(
+            inlet_data.socket_addr,
+            Some("127.0.0.1:1000".parse().unwrap())
+        );
+
+        // A project id which can't be part of a route is rejected
+        if let Some(details) = invitation.service_access_details.as_mut() {
+            details.enrollment_ticket = EnrollmentTicket::new(
+                OneTimeCode::new(),
+                Some(ProjectLookup {
+                    node_route: None,
+                    id: "invalid/project id".to_string(),
+                    name: "project_name".to_string(),
+                    identity_id: None,
+                    authority: None,
+                    okta: None,
+                }),
+                None,
+            )
+            .hex_encoded()
+            .unwrap();
+        }
+        let err = InletDataFromInvitation::new(&cli_state, &invitation, &inlets).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Invalid service route /project/invalid/project id/service"));
+    }
+
+    #[tokio::test]
+    async fn test_create_inlet_reuses_socket_addr() {
+        let client = MockBackgroundNodeClient::default();
+        let mut inlet_data = InletDataFromInvitation {
+            enabled: true,
+            local_node_name: "local_node_name".to_string(),
+            service_name: "service_name".to_string(),
+            service_route: "service_route".to_string(),
+            enrollment_ticket_hex: None,
+            socket_addr: Some("127.0.0.1:1000".parse().unwrap()),
+        };
+
+        // the socket address assigned on a previous refresh is used to bind the inlet
+        let socket_addr = create_inlet(Arc::new(client.clone()), &inlet_data)
+            .await
+            .unwrap();
+        assert_eq!(socket_addr, "127.0.0.1:1000".parse().unwrap());
+        assert_eq!(
+            client.calls(),
+            vec![
+                "create node local_node_name".to_string(),
+                "create inlet local_node_name 127.0.0.1:1000".to_string()
+            ]
+        );
+
+        // without a previous socket address, a new one is allocated
+        inlet_data.socket_addr = None;
+        let socket_addr = create_inlet(Arc::new(client.clone()), &inlet_data)
+            .await
+            .unwrap();
+        assert_eq!(
+            client.calls().last().unwrap(),
+            &format!("create inlet local_node_name {socket_addr}")
+        );
     }
 }
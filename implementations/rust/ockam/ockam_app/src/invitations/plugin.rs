@@ -17,15 +17,20 @@ const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
 
 pub(crate) fn init<R: Runtime>() -> TauriPlugin<R> {
     Builder::new("invitations")
-        .invoke_handler(tauri::generate_handler![create_service_invitation,])
+        .invoke_handler(tauri::generate_handler![
+            create_service_invitation,
+            list_sent_invitations,
+            list_received_invitations,
+        ])
         .setup(|app, _api| {
             debug!("Initializing the invitations plugin");
             app.manage(Arc::new(RwLock::new(InvitationState::default())));
 
             let handle = app.clone();
-            app.listen_global(REFRESH_INVITATIONS, move |_event| {
+            app.listen_global(REFRESH_INVITATIONS, move |event| {
                 let app_state = handle.state::<AppState>();
-                let event_tracker = app_state.debounce_event(&handle, REFRESH_INVITATIONS);
+                let event_tracker =
+                    app_state.debounce_event(&handle, REFRESH_INVITATIONS, event.payload());
                 if event_tracker.is_processing() {
                     return;
                 }
@@ -45,6 +50,8 @@ pub(crate) fn init<R: Runtime>() -> TauriPlugin<R> {
 
             let handle = app.clone();
             spawn(async move {
+                // the persisted inlets must be restored before the first refresh
+                restore_inlets(&handle).await;
                 let mut interval = tokio::time::interval(DEFAULT_POLL_INTERVAL);
                 loop {
                     interval.tick().await;
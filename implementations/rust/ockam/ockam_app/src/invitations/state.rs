@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
 
@@ -7,9 +7,11 @@ use tauri::async_runtime::RwLock;
 use tracing::debug;
 
 use ockam_api::cloud::share::{
-    InvitationList, InvitationWithAccess, ReceivedInvitation, SentInvitation,
+    InvitationList, InvitationWithAccess, ReceivedInvitation, RoleInShare, SentInvitation,
+    ShareScope,
 };
 
+use crate::app::ModelState;
 use crate::invitations::commands::InletDataFromInvitation;
 use crate::{error::Error, Result};
 
@@ -21,6 +23,9 @@ pub struct InvitationState {
     pub(crate) received: ReceivedInvitations,
     #[serde(default)]
     pub(crate) accepted: AcceptedInvitations,
+    /// Invitations which could not be sent, even after retrying
+    #[serde(default)]
+    pub(crate) failed: Vec<FailedInvitation>,
 }
 
 impl InvitationState {
@@ -29,9 +34,68 @@ impl InvitationState {
         self.sent = list.sent.unwrap_or_default();
         self.received.invitations = list.received.unwrap_or_default();
         self.accepted.invitations = list.accepted.unwrap_or_default();
+        let deleted = &self.accepted.deleted;
+        self.accepted
+            .invitations
+            .retain(|i| !deleted.contains(&i.invitation.id));
+    }
+
+    /// Add a newly sent invitation, replacing any invitation with the same id.
+    /// A previous failure to send an invitation to the same recipient is discarded.
+    pub(crate) fn add_sent(&mut self, invitation: SentInvitation) {
+        self.failed
+            .retain(|f| f.recipient_email != invitation.recipient_email);
+        self.sent.retain(|i| i.id != invitation.id);
+        self.sent.push(invitation);
+    }
+
+    /// Return the sent invitations matching the given scope and role.
+    /// A `None` filter matches any value
+    pub(crate) fn list_sent_invitations(
+        &self,
+        scope: Option<ShareScope>,
+        role: Option<RoleInShare>,
+    ) -> Vec<&SentInvitation> {
+        self.sent
+            .iter()
+            .filter(|i| matches_filter(&i.scope, &scope) && matches_filter(&i.grant_role, &role))
+            .collect()
+    }
+
+    /// Return the received invitations matching the given scope and role.
+    /// A `None` filter matches any value
+    pub(crate) fn list_received_invitations(
+        &self,
+        scope: Option<ShareScope>,
+        role: Option<RoleInShare>,
+    ) -> Vec<&ReceivedInvitation> {
+        self.received
+            .invitations
+            .iter()
+            .filter(|i| matches_filter(&i.scope, &scope) && matches_filter(&i.grant_role, &role))
+            .collect()
+    }
+
+    /// Record that an invitation to the given recipient could not be sent
+    pub(crate) fn add_failed(&mut self, recipient_email: String, error: String) {
+        self.failed.retain(|f| f.recipient_email != recipient_email);
+        self.failed.push(FailedInvitation {
+            recipient_email,
+            error,
+        });
     }
 }
 
+fn matches_filter<T: PartialEq>(value: &T, filter: &Option<T>) -> bool {
+    filter.as_ref().map(|f| f == value).unwrap_or(true)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FailedInvitation {
+    pub(crate) recipient_email: String,
+    pub(crate) error: String,
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct ReceivedInvitations {
     pub(crate) invitations: Vec<ReceivedInvitation>,
@@ -40,10 +104,31 @@ pub struct ReceivedInvitations {
     pub(crate) status: Vec<(String, ReceivedInvitationStatus)>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+impl ReceivedInvitations {
+    /// Check if the received invitation with the given id has expired.
+    /// In that case, its status is set to `Expired` and `true` is returned.
+    pub(crate) fn check_expired(&mut self, id: &str) -> Result<bool> {
+        let is_expired = match self.invitations.iter().find(|i| i.id == id) {
+            Some(invitation) => invitation.is_expired()?,
+            None => false,
+        };
+        if is_expired {
+            match self.status.iter_mut().find(|x| x.0 == id) {
+                Some((_, status)) => *status = ReceivedInvitationStatus::Expired,
+                None => self
+                    .status
+                    .push((id.to_string(), ReceivedInvitationStatus::Expired)),
+            }
+        }
+        Ok(is_expired)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum ReceivedInvitationStatus {
     Accepting,
     Accepted,
+    Expired,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -54,9 +139,24 @@ pub struct AcceptedInvitations {
     /// Inlets for accepted invitations, keyed by invitation id.
     #[serde(default)]
     pub(crate) inlets: HashMap<String, Inlet>,
+
+    /// Ids of the accepted invitations deleted by the user, which are ignored when refreshing
+    #[serde(default)]
+    pub(crate) deleted: HashSet<String>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+impl AcceptedInvitations {
+    /// Remove an accepted invitation and mark it as deleted so that it is ignored
+    /// by the next refreshes. Return the inlet created for that invitation, if any
+    pub(crate) fn delete(&mut self, invitation_id: &str) -> Option<Inlet> {
+        self.deleted.insert(invitation_id.to_string());
+        self.invitations
+            .retain(|i| i.invitation.id != invitation_id);
+        self.inlets.remove(invitation_id)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub(crate) struct Inlet {
     pub(crate) node_name: String,
     pub(crate) alias: String,
@@ -87,12 +187,35 @@ impl Inlet {
     }
 }
 
+impl ModelState {
+    /// Add the inlet created for an accepted invitation to the model,
+    /// replacing any inlet previously stored for that invitation
+    pub(crate) fn add_inlet(&mut self, invitation_id: &str, inlet: Inlet) {
+        self.inlets.insert(invitation_id.to_string(), inlet);
+    }
+
+    pub(crate) fn delete_inlet(&mut self, invitation_id: &str) {
+        self.inlets.remove(invitation_id);
+    }
+
+    pub(crate) fn get_inlets(&self) -> &HashMap<String, Inlet> {
+        &self.inlets
+    }
+
+    pub(crate) fn add_deleted_invitation(&mut self, invitation_id: &str) {
+        self.deleted_invitations.insert(invitation_id.to_string());
+    }
+
+    pub(crate) fn get_deleted_invitations(&self) -> &HashSet<String> {
+        &self.deleted_invitations
+    }
+}
+
 pub(crate) type SyncInvitationsState = Arc<RwLock<InvitationState>>;
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ockam_api::cloud::share::{RoleInShare, ShareScope};
 
     #[test]
     fn test_replace_by() {
@@ -136,4 +259,171 @@ mod tests {
         assert_eq!(state.received.invitations.len(), 1);
         assert_eq!(state.accepted.invitations.len(), 1);
     }
+
+    #[test]
+    fn test_deleted_accepted_invitation_is_not_refreshed() {
+        let accepted = InvitationWithAccess {
+            invitation: ReceivedInvitation {
+                id: "id".to_string(),
+                expires_at: "expires_at".to_string(),
+                grant_role: RoleInShare::Admin,
+                owner_email: "owner_email".to_string(),
+                scope: ShareScope::Service,
+                target_id: "target_id".to_string(),
+            },
+            service_access_details: None,
+        };
+        let list = || InvitationList {
+            sent: None,
+            received: None,
+            accepted: Some(vec![accepted.clone()]),
+        };
+        let inlet = Inlet {
+            node_name: "node".to_string(),
+            alias: "alias".to_string(),
+            socket_addr: "127.0.0.1:5000".parse().unwrap(),
+            enabled: true,
+        };
+        let mut state = InvitationState::default();
+        state.replace_by(list());
+        state
+            .accepted
+            .inlets
+            .insert("id".to_string(), inlet.clone());
+
+        // the inlet is returned so that it can be deleted with its node
+        assert_eq!(state.accepted.delete("id"), Some(inlet));
+        assert!(state.accepted.invitations.is_empty());
+        assert!(state.accepted.inlets.is_empty());
+
+        // the controller still returns the invitation, but it is not brought back
+        state.replace_by(list());
+        assert!(state.accepted.invitations.is_empty());
+
+        // there is no inlet to delete when deleting the invitation again
+        assert_eq!(state.accepted.delete("id"), None);
+    }
+
+    #[test]
+    fn test_check_expired() {
+        let invitation = |id: &str, expires_at: &str| ReceivedInvitation {
+            id: id.to_string(),
+            expires_at: expires_at.to_string(),
+            grant_role: RoleInShare::Admin,
+            owner_email: "owner_email".to_string(),
+            scope: ShareScope::Project,
+            target_id: "target_id".to_string(),
+        };
+        let mut received = ReceivedInvitations {
+            invitations: vec![
+                invitation("expired", "2020-09-12T15:07:14.00"),
+                invitation("valid", "2100-09-12T15:07:14.00"),
+            ],
+            status: vec![],
+        };
+
+        assert!(received.check_expired("expired").unwrap());
+        assert_eq!(
+            received.status,
+            vec![("expired".to_string(), ReceivedInvitationStatus::Expired)]
+        );
+
+        assert!(!received.check_expired("valid").unwrap());
+        assert!(!received.check_expired("unknown").unwrap());
+        assert_eq!(received.status.len(), 1);
+    }
+
+    #[test]
+    fn test_add_sent_and_failed() {
+        let invitation = SentInvitation {
+            id: "id".to_string(),
+            expires_at: "expires_at".to_string(),
+            grant_role: RoleInShare::Admin,
+            owner_id: 0,
+            recipient_email: "user@example.com".to_string(),
+            remaining_uses: 0,
+            scope: ShareScope::Service,
+            target_id: "target_id".to_string(),
+        };
+        let mut state = InvitationState::default();
+
+        state.add_failed("user@example.com".to_string(), "error".to_string());
+        state.add_failed("user@example.com".to_string(), "other error".to_string());
+        assert_eq!(
+            state.failed,
+            vec![FailedInvitation {
+                recipient_email: "user@example.com".to_string(),
+                error: "other error".to_string(),
+            }]
+        );
+
+        // a successful send clears the failure and is only stored once
+        state.add_sent(invitation.clone());
+        state.add_sent(invitation);
+        assert!(state.failed.is_empty());
+        assert_eq!(state.sent.len(), 1);
+    }
+
+    #[test]
+    fn test_list_invitations_with_filters() {
+        let sent = |id: &str, scope: ShareScope, grant_role: RoleInShare| SentInvitation {
+            id: id.to_string(),
+            expires_at: "expires_at".to_string(),
+            grant_role,
+            owner_id: 0,
+            recipient_email: "recipient_email".to_string(),
+            remaining_uses: 0,
+            scope,
+            target_id: "target_id".to_string(),
+        };
+        let received = |id: &str, scope: ShareScope, grant_role: RoleInShare| ReceivedInvitation {
+            id: id.to_string(),
+            expires_at: "expires_at".to_string(),
+            grant_role,
+            owner_email: "owner_email".to_string(),
+            scope,
+            target_id: "target_id".to_string(),
+        };
+        let mut state = InvitationState::default();
+        state.replace_by(InvitationList {
+            sent: Some(vec![
+                sent("s1", ShareScope::Project, RoleInShare::Admin),
+                sent("s2", ShareScope::Project, RoleInShare::Guest),
+                sent("s3", ShareScope::Service, RoleInShare::Service),
+            ]),
+            received: Some(vec![
+                received("r1", ShareScope::Space, RoleInShare::Admin),
+                received("r2", ShareScope::Service, RoleInShare::Service),
+            ]),
+            accepted: None,
+        });
+        let sent_ids = |scope, role| {
+            state
+                .list_sent_invitations(scope, role)
+                .into_iter()
+                .map(|i| i.id.clone())
+                .collect::<Vec<_>>()
+        };
+        let received_ids = |scope, role| {
+            state
+                .list_received_invitations(scope, role)
+                .into_iter()
+                .map(|i| i.id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sent_ids(None, None), vec!["s1", "s2", "s3"]);
+        assert_eq!(sent_ids(Some(ShareScope::Project), None), vec!["s1", "s2"]);
+        assert_eq!(sent_ids(None, Some(RoleInShare::Service)), vec!["s3"]);
+        assert_eq!(
+            sent_ids(Some(ShareScope::Project), Some(RoleInShare::Admin)),
+            vec!["s1"]
+        );
+        assert!(sent_ids(Some(ShareScope::Space), None).is_empty());
+
+        assert_eq!(received_ids(None, None), vec!["r1", "r2"]);
+        assert_eq!(received_ids(Some(ShareScope::Service), None), vec!["r2"]);
+        assert_eq!(received_ids(None, Some(RoleInShare::Admin)), vec!["r1"]);
+        assert!(received_ids(Some(ShareScope::Space), Some(RoleInShare::Guest)).is_empty());
+    }
 }
@@ -15,7 +15,7 @@ use super::state::SyncInvitationsState;
 use crate::app::AppState;
 use crate::icons::themed_icon;
 use crate::invitations::state::{
-    AcceptedInvitations, Inlet, ReceivedInvitationStatus, ReceivedInvitations,
+    AcceptedInvitations, FailedInvitation, Inlet, ReceivedInvitationStatus, ReceivedInvitations,
 };
 
 pub const INVITATIONS_WINDOW_ID: &str = "invitations_creation";
@@ -95,6 +95,20 @@ pub(crate) fn pending_invitation_menu<R: Runtime>(
         .expect("cannot build single invitation submenu")
 }
 
+pub(crate) fn failed_invitation_menu<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    invitation: &FailedInvitation,
+) -> Submenu<R> {
+    SubmenuBuilder::new(app_handle, &invitation.recipient_email)
+        .items(&[
+            &MenuItemBuilder::new(format!("Error: {}", invitation.error))
+                .enabled(false)
+                .build(app_handle),
+        ])
+        .build()
+        .expect("cannot build failed invitation submenu")
+}
+
 fn add_received_menu<R: Runtime>(
     app_handle: &AppHandle<R>,
     received: &ReceivedInvitations,
@@ -146,6 +160,9 @@ fn received_invite_menu<R: Runtime>(
             ReceivedInvitationStatus::Accepted => {
                 IconMenuItemBuilder::new("Invitation accepted").enabled(false)
             }
+            ReceivedInvitationStatus::Expired => {
+                IconMenuItemBuilder::new("Invitation expired").enabled(false)
+            }
         })
         .unwrap_or(IconMenuItemBuilder::with_id(
             format!("invitation-received-accept-{}", invitation.id),
@@ -262,6 +279,14 @@ fn accepted_invite_menu<R: Runtime>(
                 .build(app_handle),
         ),
     };
+    submenu_builder = submenu_builder.item(
+        &IconMenuItemBuilder::with_id(
+            format!("invitation-accepted-delete-{invitation_id}"),
+            "Remove",
+        )
+        .icon(Icon::Raw(themed_icon("trash3")))
+        .build(app_handle),
+    );
     submenu_builder
         .build()
         .expect("cannot build accepted invitation submenu")
@@ -290,6 +315,7 @@ fn dispatch_click_event<R: Runtime>(app: &AppHandle<R>, id: &str) -> tauri::Resu
         ["accepted", "copy", socket_address] => on_copy(app, socket_address),
         ["accepted", "disconnect", id] => on_disconnect(app, id),
         ["accepted", "connect", id] => on_connect(app, id),
+        ["accepted", "delete", id] => on_delete(app, id),
         other => {
             warn!(?other, "unexpected menu ID");
             Ok(())
@@ -365,3 +391,15 @@ fn on_connect<R: Runtime>(app: &AppHandle<R>, invitation_id: &str) -> tauri::Res
     });
     Ok(())
 }
+
+fn on_delete<R: Runtime>(app: &AppHandle<R>, invitation_id: &str) -> tauri::Result<()> {
+    debug!(%invitation_id, "Invite on_delete clicked");
+    let app = app.clone();
+    let invitation_id = invitation_id.to_string();
+    spawn(async move {
+        let _ = super::commands::delete_accepted_invitation(app, &invitation_id)
+            .await
+            .map_err(|e| error!(%e, "Failed to delete accepted invitation"));
+    });
+    Ok(())
+}
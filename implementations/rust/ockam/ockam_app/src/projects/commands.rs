@@ -7,6 +7,7 @@ use ockam_api::cloud::project::Projects;
 use ockam_api::{cli_state::StateDirTrait, cloud::project::Project, identity::EnrollmentTicket};
 
 use crate::app::AppState;
+use crate::background_node::BackgroundNodeClient;
 use crate::projects::error::Error::{InternalFailure, ListingFailed, StateSaveFailed};
 
 use super::error::{Error, Result};
@@ -15,24 +16,16 @@ use super::State as ProjectState;
 // Store the user's admin projects
 pub type SyncAdminProjectsState = Arc<RwLock<ProjectState>>;
 
-pub(crate) async fn create_enrollment_ticket<R: Runtime>(
-    project_id: String,
-    app: AppHandle<R>,
+/// Create an enrollment ticket for the project with the given name
+pub(crate) async fn create_enrollment_ticket(
+    background_node_client: Arc<dyn BackgroundNodeClient>,
+    project_name: &str,
 ) -> Result<EnrollmentTicket> {
-    let app_state: State<'_, AppState> = app.state();
-    let projects_state: State<'_, SyncAdminProjectsState> = app.state();
-    let projects = projects_state.read().await;
-    let project = projects
-        .iter()
-        .find(|p| p.id == project_id)
-        .ok_or_else(|| Error::ProjectNotFound(project_id.to_owned()))?;
-
-    debug!(?project_id, "Creating enrollment ticket via CLI");
+    debug!(?project_name, "Creating enrollment ticket via CLI");
     // TODO: How might this degrade for users who have multiple spaces and projects?
-    let background_node_client = app_state.background_node_client().await;
     let hex_encoded_ticket = background_node_client
         .projects()
-        .ticket(&project.name)
+        .ticket(project_name)
         .await
         .map_err(|_| Error::EnrollmentTicketFailed)?;
     serde_json::from_slice(&hex::decode(hex_encoded_ticket).map_err(|err| {
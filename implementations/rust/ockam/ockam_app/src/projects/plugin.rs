@@ -24,9 +24,10 @@ pub(crate) fn init<R: Runtime>() -> TauriPlugin<R> {
             app.manage(Arc::new(RwLock::new(State::default())));
 
             let handle = app.clone();
-            app.listen_global(REFRESH_PROJECTS, move |_event| {
+            app.listen_global(REFRESH_PROJECTS, move |event| {
                 let app_state = handle.state::<AppState>();
-                let event_tracker = app_state.debounce_event(&handle, REFRESH_PROJECTS);
+                let event_tracker =
+                    app_state.debounce_event(&handle, REFRESH_PROJECTS, event.payload());
                 if event_tracker.is_processing() {
                     return;
                 }
@@ -5,28 +5,99 @@ use ockam::Context;
 use ockam_api::cli_state::{CliState, StateDirTrait};
 use ockam_api::nodes::models::relay::RelayInfo;
 use ockam_api::nodes::InMemoryNode;
+use ockam_core::async_trait;
+use ockam_core::env::get_env_with_default;
 use ockam_multiaddr::MultiAddr;
 use once_cell::sync::Lazy;
+use std::future::Future;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio_retry::strategy::ExponentialBackoff;
 use tracing::{debug, info, trace, warn};
 
 pub static RELAY_NAME: Lazy<String> = Lazy::new(|| format!("forward_to_{NODE_NAME}"));
 
-/// Try to create a relay until it succeeds.
+/// Default maximum delay, in seconds, between two relay creation attempts.
+/// It can be changed with the `OCKAM_RELAY_RETRY_MAX_SECS` environment variable
+const DEFAULT_RELAY_RETRY_MAX_SECS: u64 = 60;
+
+/// Maximum number of retries after a failed relay creation
+const MAX_RELAY_CREATION_RETRIES: usize = 20;
+
+/// Try to create a relay until it succeeds or the retries are exhausted.
+///
+/// The lock makes sure that only one relay creation attempt runs at a time. It is only held
+/// during an attempt, so that an attempt started while another one is waiting to be retried
+/// can proceed, and find the existing relay if it was created in the meantime.
 pub async fn create_relay(
     context: Arc<Context>,
     cli_state: CliState,
     node_manager: Arc<InMemoryNode>,
+    lock: Arc<Mutex<()>>,
 ) {
+    let node_manager = InMemoryNodeRelays {
+        context,
+        node_manager,
+    };
+    create_relay_exclusively(
+        lock,
+        || create_relay_impl(&cli_state, &node_manager),
+        relay_retry_strategy(),
+    )
+    .await
+}
+
+/// Return the delays between relay creation attempts, capped by `OCKAM_RELAY_RETRY_MAX_SECS`.
+/// A new strategy is used for each relay creation, so the delay is reset after a success
+fn relay_retry_strategy() -> impl Iterator<Item = Duration> {
+    let max_secs = get_env_with_default("OCKAM_RELAY_RETRY_MAX_SECS", DEFAULT_RELAY_RETRY_MAX_SECS)
+        .unwrap_or_else(|e| {
+            warn!(%e, "Invalid value for OCKAM_RELAY_RETRY_MAX_SECS, using the default value");
+            DEFAULT_RELAY_RETRY_MAX_SECS
+        });
+    relay_retry_strategy_with_max_delay(Duration::from_secs(max_secs))
+}
+
+/// Return an exponential backoff starting at 2 seconds and capped by the given delay,
+/// for at most `MAX_RELAY_CREATION_RETRIES` retries
+fn relay_retry_strategy_with_max_delay(max_delay: Duration) -> impl Iterator<Item = Duration> {
+    ExponentialBackoff::from_millis(2)
+        .factor(1000)
+        .max_delay(max_delay)
+        .take(MAX_RELAY_CREATION_RETRIES)
+}
+
+/// Run the relay creation attempts until one succeeds or the retry strategy ends.
+/// Each attempt holds the lock, which is released while waiting for the next attempt.
+async fn create_relay_exclusively<F, Fut>(
+    lock: Arc<Mutex<()>>,
+    mut create: F,
+    retry_strategy: impl IntoIterator<Item = Duration>,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<RelayInfo>>>,
+{
+    let mut delays = retry_strategy.into_iter();
     loop {
-        match create_relay_impl(&context, &cli_state, node_manager.clone()).await {
+        let result = {
+            let _guard = lock.lock().await;
+            create().await
+        };
+        match result {
             Ok(_) => break,
-            Err(e) => {
-                warn!(%e, "Failed to create relay, retrying...");
-            }
+            Err(e) => match delays.next() {
+                Some(delay) => {
+                    warn!(%e, ?delay, "Failed to create relay, retrying...");
+                    tokio::time::sleep(delay).await;
+                }
+                None => {
+                    warn!(%e, "Failed to create relay, giving up");
+                    break;
+                }
+            },
         }
-        tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
     }
 }
 
@@ -34,38 +105,18 @@ pub async fn create_relay(
 ///
 /// Once it's created, a `Medic` worker will monitor it and recreate it whenever it's unresponsive
 async fn create_relay_impl(
-    context: &Context,
     cli_state: &CliState,
-    node_manager: Arc<InMemoryNode>,
+    node_manager: &impl RelayNodeManager,
 ) -> Result<Option<RelayInfo>> {
     trace!("Creating relay");
-    if !cli_state.is_enrolled().unwrap_or(false) {
+    if !cli_state.is_fully_provisioned().unwrap_or(false) {
         trace!("Not enrolled, skipping relay creation");
         return Ok(None);
     }
     match cli_state.projects.default() {
-        Ok(project) => {
-            if let Some(relay) = get_relay(node_manager.clone()).await {
-                debug!(project = %project.name(), "Relay already exists");
-                Ok(Some(relay.clone()))
-            } else {
-                debug!(project = %project.name(), "Creating relay at project");
-                let project_route = format!("/project/{}", project.name());
-                let project_address = MultiAddr::from_str(&project_route).into_diagnostic()?;
-                let relay = node_manager
-                    .create_relay(
-                        context,
-                        &project_address,
-                        Some(NODE_NAME.to_string()),
-                        false,
-                        None,
-                    )
-                    .await
-                    .into_diagnostic()?;
-                info!(forwarding_route = %relay.forwarding_route(), "Relay created at project");
-                Ok(Some(relay))
-            }
-        }
+        Ok(project) => Ok(Some(
+            create_project_relay(node_manager, project.name()).await?,
+        )),
         Err(err) => {
             warn!(%err, "No default project has ben set");
             Ok(None)
@@ -73,10 +124,216 @@ async fn create_relay_impl(
     }
 }
 
+/// Create a relay at the given project, unless it already exists
+async fn create_project_relay(
+    node_manager: &impl RelayNodeManager,
+    project_name: &str,
+) -> Result<RelayInfo> {
+    if let Some(relay) = find_relay(node_manager.get_relays().await) {
+        debug!(project = %project_name, "Relay already exists");
+        return Ok(relay);
+    }
+    debug!(project = %project_name, "Creating relay at project");
+    let project_route = format!("/project/{project_name}");
+    let project_address = MultiAddr::from_str(&project_route).into_diagnostic()?;
+    let relay = node_manager
+        .create_relay(&project_address, Some(NODE_NAME.to_string()))
+        .await?;
+    info!(forwarding_route = %relay.forwarding_route(), "Relay created at project");
+    Ok(relay)
+}
+
 pub(crate) async fn get_relay(node_manager: Arc<InMemoryNode>) -> Option<RelayInfo> {
-    node_manager
-        .get_relays()
-        .await
+    find_relay(node_manager.get_relays().await)
+}
+
+fn find_relay(relays: Vec<RelayInfo>) -> Option<RelayInfo> {
+    relays
         .into_iter()
         .find(|r| r.remote_address() == *RELAY_NAME)
 }
+
+/// The node manager operations needed to create the relay
+#[async_trait]
+trait RelayNodeManager: Send + Sync {
+    /// Return the relays of the node
+    async fn get_relays(&self) -> Vec<RelayInfo>;
+
+    /// Create a relay at the given address
+    async fn create_relay(&self, address: &MultiAddr, alias: Option<String>) -> Result<RelayInfo>;
+}
+
+/// Relays of the in-memory node manager of the application
+struct InMemoryNodeRelays {
+    context: Arc<Context>,
+    node_manager: Arc<InMemoryNode>,
+}
+
+#[async_trait]
+impl RelayNodeManager for InMemoryNodeRelays {
+    async fn get_relays(&self) -> Vec<RelayInfo> {
+        self.node_manager.get_relays().await
+    }
+
+    async fn create_relay(&self, address: &MultiAddr, alias: Option<String>) -> Result<RelayInfo> {
+        Ok(self
+            .node_manager
+            .create_relay(&self.context, address, alias, false, None)
+            .await
+            .into_diagnostic()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// This node manager records the relay creations.
+    /// A relay creation takes some time, so that concurrent relay creations overlap
+    #[derive(Default)]
+    struct MockNodeManager {
+        relays: std::sync::Mutex<Vec<RelayInfo>>,
+        creations: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl RelayNodeManager for MockNodeManager {
+        async fn get_relays(&self) -> Vec<RelayInfo> {
+            self.relays.lock().unwrap().clone()
+        }
+
+        async fn create_relay(
+            &self,
+            _address: &MultiAddr,
+            _alias: Option<String>,
+        ) -> Result<RelayInfo> {
+            self.creations.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let relay: RelayInfo = serde_json::from_value(serde_json::json!({
+                "forwarding_route": "forwarding_route",
+                "remote_address": *RELAY_NAME,
+                "worker_address": "worker_address",
+                "flow_control_id": null,
+            }))?;
+            self.relays.lock().unwrap().push(relay.clone());
+            Ok(relay)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_relay_creations_create_a_single_relay() {
+        let node_manager = Arc::new(MockNodeManager::default());
+        let lock = Arc::new(Mutex::new(()));
+
+        let tasks: Vec<_> = (0..5)
+            .map(|_| {
+                let node_manager = node_manager.clone();
+                let lock = lock.clone();
+                tokio::spawn(async move {
+                    create_relay_exclusively(
+                        lock,
+                        || {
+                            let node_manager = node_manager.clone();
+                            async move {
+                                let relay =
+                                    create_project_relay(node_manager.as_ref(), "project").await?;
+                                Ok(Some(relay))
+                            }
+                        },
+                        vec![],
+                    )
+                    .await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(node_manager.creations.load(Ordering::SeqCst), 1);
+        assert_eq!(node_manager.get_relays().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_the_lock_is_released_while_waiting_for_a_retry() {
+        let lock = Arc::new(Mutex::new(()));
+
+        // the first relay creation fails and waits a long time before retrying
+        let failing = tokio::spawn(create_relay_exclusively(
+            lock.clone(),
+            || async { Err("relay creation failed".into()) },
+            vec![Duration::from_secs(60)],
+        ));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // another relay creation can proceed in the meantime
+        let succeeded = Arc::new(AtomicBool::new(false));
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            create_relay_exclusively(
+                lock,
+                || {
+                    let succeeded = succeeded.clone();
+                    async move {
+                        succeeded.store(true, Ordering::SeqCst);
+                        Ok(None)
+                    }
+                },
+                vec![],
+            ),
+        )
+        .await
+        .unwrap();
+        assert!(succeeded.load(Ordering::SeqCst));
+        failing.abort();
+    }
+
+    #[test]
+    fn test_relay_retry_strategy() {
+        let delays: Vec<u64> = relay_retry_strategy_with_max_delay(Duration::from_secs(60))
+            .map(|d| d.as_secs())
+            .collect();
+        assert_eq!(delays.len(), MAX_RELAY_CREATION_RETRIES);
+        assert_eq!(delays[..7], [2, 4, 8, 16, 32, 60, 60]);
+    }
+
+    #[tokio::test]
+    async fn test_relay_creation_is_retried_with_the_retry_strategy() {
+        // the relay creation fails 3 times, then succeeds
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let create = {
+            let attempts = attempts.clone();
+            move || {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt < 3 {
+                        Err("relay creation failed".into())
+                    } else {
+                        Ok(None)
+                    }
+                }
+            }
+        };
+
+        // record the delays used between attempts
+        let delays = Arc::new(std::sync::Mutex::new(vec![]));
+        let strategy = {
+            let delays = delays.clone();
+            ExponentialBackoff::from_millis(2)
+                .max_delay(Duration::from_millis(5))
+                .inspect(move |d| delays.lock().unwrap().push(*d))
+        };
+        create_relay_exclusively(Arc::new(Mutex::new(())), create, strategy).await;
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            *delays.lock().unwrap(),
+            vec![
+                Duration::from_millis(2),
+                Duration::from_millis(4),
+                Duration::from_millis(5)
+            ]
+        );
+    }
+}
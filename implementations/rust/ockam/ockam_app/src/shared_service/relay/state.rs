@@ -2,14 +2,22 @@ use ockam::Context;
 use ockam_api::cli_state::CliState;
 use ockam_api::nodes::InMemoryNode;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 
 pub(crate) fn load_model_state(
     context: Arc<Context>,
     node_manager: Arc<InMemoryNode>,
     cli_state: &CliState,
+    relay_creation_lock: Arc<Mutex<()>>,
 ) {
     let cli_state = cli_state.clone();
     tauri::async_runtime::spawn(async move {
-        super::create_relay(context, cli_state, node_manager.clone()).await;
+        super::create_relay(
+            context,
+            cli_state,
+            node_manager.clone(),
+            relay_creation_lock,
+        )
+        .await;
     });
 }
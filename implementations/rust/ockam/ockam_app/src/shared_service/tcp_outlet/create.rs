@@ -60,7 +60,7 @@ async fn tcp_outlet_create_impl<R: Runtime>(
     {
         Ok(status) => {
             info!(socket_addr = socket_addr.to_string(), "Outlet created");
-            app_state.model_mut(|m| m.add_tcp_outlet(status)).await?;
+            app_state.add_tcp_outlet(status).await?;
             system_tray_on_update(&app);
             Ok(())
         }
@@ -22,7 +22,7 @@ async fn tcp_outlet_delete_impl<R: Runtime>(app: AppHandle<R>, alias: String) ->
     match node_manager.delete_outlet(&alias).await {
         Ok(_) => {
             info!(%alias, "TCP outlet deleted");
-            app_state.model_mut(|m| m.delete_tcp_outlet(&alias)).await?;
+            app_state.delete_tcp_outlet(&alias).await?;
             system_tray_on_update(&app);
             Ok(())
         }
@@ -7,7 +7,10 @@ use std::sync::Arc;
 use tracing::{debug, error};
 
 impl ModelState {
+    /// Add a TCP outlet to the model.
+    /// Outlets are identified by their alias, so an existing outlet with the same alias is replaced
     pub fn add_tcp_outlet(&mut self, status: OutletStatus) {
+        self.delete_tcp_outlet(&status.alias);
         self.tcp_outlets.push(status);
     }
 
@@ -26,7 +29,7 @@ pub(crate) async fn load_model_state(
     model_state: &ModelState,
     cli_state: &CliState,
 ) {
-    if !cli_state.is_enrolled().unwrap_or(false) {
+    if !cli_state.is_fully_provisioned().unwrap_or(false) {
         return;
     }
     for tcp_outlet in model_state.get_tcp_outlets() {
@@ -9,8 +9,8 @@ use ockam_api::nodes::models::portal::OutletStatus;
 
 use crate::app::AppState;
 use crate::icons::themed_icon;
-use crate::invitations::pending_invitation_menu;
 use crate::invitations::state::SyncInvitationsState;
+use crate::invitations::{failed_invitation_menu, pending_invitation_menu};
 use crate::shared_service::tcp_outlet::tcp_outlet_delete;
 
 const SHARED_SERVICE_CREATE_MENU_ID: &str = "shared-service-create";
@@ -67,6 +67,21 @@ pub(crate) async fn build_shared_services_section<'a, R: Runtime, M: Manager<R>>
                 .expect("cannot build menu for pending invitations"),
         )
     };
+    builder = if reader.failed.is_empty() {
+        builder
+    } else {
+        let mut submenu = SubmenuBuilder::new(app_handle, "Failed invitations");
+        submenu = reader
+            .failed
+            .iter()
+            .map(|invitation| failed_invitation_menu(app_handle, invitation))
+            .fold(submenu, |builder, submenu| builder.item(&submenu));
+        builder.item(
+            &submenu
+                .build()
+                .expect("cannot build menu for failed invitations"),
+        )
+    };
 
     builder.separator()
 }
@@ -1,17 +1,21 @@
+use std::path::{Path, PathBuf};
+
 use ockam_core::compat::collections::HashMap;
 
 use crate::identity::{get_identity_name, initialize_identity_if_default};
 use crate::{
+    fmt_warn,
     util::{node_rpc, parsers::identity_identifier_parser},
     vault::default_vault_name,
     CommandGlobalOpts, Result,
 };
 use clap::Args;
+use colorful::Colorful;
 
 use crate::output::{CredentialAndPurposeKeyDisplay, EncodeFormat};
 use miette::{miette, IntoDiagnostic};
 use ockam::identity::utils::AttributesBuilder;
-use ockam::identity::Identifier;
+use ockam::identity::{Identifier, Identities, IdentitiesReader};
 use ockam::identity::{MAX_CREDENTIAL_VALIDITY, PROJECT_MEMBER_SCHEMA, TRUST_CONTEXT_ID};
 use ockam::Context;
 use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
@@ -29,6 +33,12 @@ pub struct IssueCommand {
     #[arg(short, long = "attribute", value_name = "ATTRIBUTE")]
     pub attributes: Vec<String>,
 
+    /// Path to a file containing attributes to be attached to the member.
+    /// The file is either a JSON object, or a CSV file, with a `.csv` extension, containing `key,value` rows.
+    /// Attributes given with `--attribute` take precedence over the attributes of this file
+    #[arg(long = "attributes-file", value_name = "PATH")]
+    pub attributes_file: Option<PathBuf>,
+
     /// Name of the Vault that will be used to issue the credential.
     #[arg(value_name = "VAULT_NAME")]
     pub vault: Option<String>,
@@ -36,6 +46,18 @@ pub struct IssueCommand {
     /// Encoding Format
     #[arg(long = "encoding", value_enum, default_value = "plain")]
     encode_format: EncodeFormat,
+
+    /// Write the credential to this file instead of printing it
+    #[arg(long = "output-file", value_name = "PATH")]
+    output_file: Option<PathBuf>,
+
+    /// Overwrite the output file if it already exists
+    #[arg(long, requires = "output_file")]
+    force: bool,
+
+    /// Fail, instead of warning, if the identity the credential is issued for is not known locally
+    #[arg(long)]
+    strict: bool,
 }
 
 impl IssueCommand {
@@ -45,7 +67,10 @@ impl IssueCommand {
     }
 
     fn attributes(&self) -> Result<HashMap<String, String>> {
-        let mut attributes = HashMap::new();
+        let mut attributes = match &self.attributes_file {
+            Some(path) => read_attributes_file(path)?,
+            None => HashMap::new(),
+        };
         for attr in &self.attributes {
             let mut parts = attr.splitn(2, '=');
             let key = parts.next().ok_or(miette!("key expected"))?;
@@ -76,6 +101,12 @@ async fn run_impl(
     let identities = opts.state.get_identities(vault).await?;
     let issuer = ident_state.identifier();
 
+    if let Some(warning) =
+        check_subject_is_known(&identities, cmd.identity_identifier(), cmd.strict).await?
+    {
+        opts.terminal.write_line(&fmt_warn!("{warning}"))?;
+    }
+
     let mut attributes_builder = AttributesBuilder::with_schema(PROJECT_MEMBER_SCHEMA)
         .with_attribute(
             TRUST_CONTEXT_ID.to_vec(),
@@ -98,8 +129,252 @@ async fn run_impl(
         .await
         .into_diagnostic()?;
 
-    cmd.encode_format
-        .println_value(&CredentialAndPurposeKeyDisplay(credential))?;
+    let credential = CredentialAndPurposeKeyDisplay(credential);
+    match &cmd.output_file {
+        Some(path) => {
+            let contents = cmd.encode_format.encode_value(&credential)?;
+            write_credential(path, &contents, cmd.force)?;
+        }
+        None => cmd.encode_format.println_value(&credential)?,
+    }
 
     Ok(())
 }
+
+/// Check that the identity a credential is issued for is known locally, in order to catch
+/// typos in identifiers. If the identity is unknown, return a warning or, in strict mode, an error
+async fn check_subject_is_known(
+    identities: &Identities,
+    identifier: &Identifier,
+    strict: bool,
+) -> miette::Result<Option<String>> {
+    let known = identities
+        .repository()
+        .retrieve_identity(identifier)
+        .await
+        .into_diagnostic()?
+        .is_some();
+    if known {
+        return Ok(None);
+    }
+    let message = format!("The identity {identifier} is not known locally");
+    if strict {
+        Err(miette!(message))
+    } else {
+        Ok(Some(message))
+    }
+}
+
+/// Read attributes from a JSON file or from a CSV file if the file has a `.csv` extension
+fn read_attributes_file(path: &Path) -> Result<HashMap<String, String>> {
+    let contents = std::fs::read_to_string(path)?;
+    let is_csv = path
+        .extension()
+        .map(|e| e.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+    if is_csv {
+        parse_csv_attributes(&contents)
+    } else {
+        parse_json_attributes(&contents)
+    }
+}
+
+/// Parse a JSON object where each value is a string, a number or a boolean
+fn parse_json_attributes(contents: &str) -> Result<HashMap<String, String>> {
+    let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(contents)?;
+    let mut attributes = HashMap::new();
+    for (key, value) in object {
+        let value = match value {
+            serde_json::Value::String(s) => s,
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::Bool(b) => b.to_string(),
+            _ => {
+                return Err(miette!(
+                    "The value of the attribute '{key}' must be a string, a number or a boolean"
+                )
+                .into())
+            }
+        };
+        attributes.insert(key, value);
+    }
+    Ok(attributes)
+}
+
+/// Parse `key,value` rows. Empty lines are skipped
+fn parse_csv_attributes(contents: &str) -> Result<HashMap<String, String>> {
+    let mut attributes = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (key, value) = line
+            .split_once(',')
+            .ok_or(miette!("Expected a 'key,value' row at line {}", index + 1))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(miette!("Missing attribute key at line {}", index + 1).into());
+        }
+        attributes.insert(key.to_string(), value.trim().to_string());
+    }
+    Ok(attributes)
+}
+
+/// Write an encoded credential to a file, creating its parent directories if necessary.
+/// An existing file is only overwritten if `force` is true
+fn write_credential(path: &Path, contents: &str, force: bool) -> miette::Result<()> {
+    if path.exists() && !force {
+        return Err(miette!(
+            "The file {} already exists. Use --force to overwrite it",
+            path.display()
+        ));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).into_diagnostic()?;
+    }
+    std::fs::write(path, contents).into_diagnostic()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credential::validate_encoded_cred;
+    use ockam::identity::identities;
+
+    #[test]
+    fn test_parse_json_attributes() {
+        let attributes =
+            parse_json_attributes(r#"{"role": "member", "level": 3, "admin": false}"#).unwrap();
+        assert_eq!(attributes.get("role").unwrap(), "member");
+        assert_eq!(attributes.get("level").unwrap(), "3");
+        assert_eq!(attributes.get("admin").unwrap(), "false");
+
+        assert!(parse_json_attributes(r#"{"role": ["member"]}"#).is_err());
+        assert!(parse_json_attributes(r#"["role"]"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_attributes() {
+        let attributes = parse_csv_attributes("role,member\n\n level , 3\nurl,a,b\n").unwrap();
+        assert_eq!(attributes.get("role").unwrap(), "member");
+        assert_eq!(attributes.get("level").unwrap(), "3");
+        assert_eq!(attributes.get("url").unwrap(), "a,b");
+
+        assert!(parse_csv_attributes("role").is_err());
+        assert!(parse_csv_attributes(",member").is_err());
+    }
+
+    #[test]
+    fn test_attributes_flags_take_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("attributes.json");
+        std::fs::write(&path, r#"{"role": "member", "team": "blue"}"#).unwrap();
+
+        let cmd = IssueCommand {
+            as_identity: None,
+            identity_identifier: Identifier::try_from("Ie92f183eb4c324804ef4d62962dea94cf095a265")
+                .unwrap(),
+            attributes: vec!["role=admin".to_string()],
+            attributes_file: Some(path),
+            vault: None,
+            encode_format: EncodeFormat::Plain,
+            output_file: None,
+            force: false,
+            strict: false,
+        };
+        let attributes = cmd.attributes().unwrap();
+        assert_eq!(attributes.get("role").unwrap(), "admin");
+        assert_eq!(attributes.get("team").unwrap(), "blue");
+    }
+
+    #[test]
+    fn test_output_file_does_not_clash_with_the_output_format() {
+        use crate::OckamCommand;
+        use clap::Parser;
+
+        let command = OckamCommand::try_parse_from([
+            "ockam",
+            "credential",
+            "issue",
+            "--for",
+            "Ie92f183eb4c324804ef4d62962dea94cf095a265",
+            "--output-file",
+            "credential.txt",
+            "--force",
+            "--output",
+            "json",
+        ]);
+        assert!(command.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_check_subject_is_known() {
+        let identities = identities();
+        let subject = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let unknown = Identifier::try_from("Ie92f183eb4c324804ef4d62962dea94cf095a265").unwrap();
+
+        // a known identity is accepted in both modes
+        for strict in [false, true] {
+            let result = check_subject_is_known(&identities, subject.identifier(), strict).await;
+            assert!(result.unwrap().is_none());
+        }
+
+        // an unknown identity triggers a warning, or an error in strict mode
+        let warning = check_subject_is_known(&identities, &unknown, false)
+            .await
+            .unwrap();
+        assert!(warning.unwrap().contains(&unknown.to_string()));
+        assert!(check_subject_is_known(&identities, &unknown, true)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_write_credential() {
+        let identities = identities();
+        let issuer = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let subject = identities
+            .identities_creation()
+            .create_identity()
+            .await
+            .unwrap();
+        let credential = identities
+            .credentials()
+            .credentials_creation()
+            .issue_credential(
+                issuer.identifier(),
+                subject.identifier(),
+                AttributesBuilder::with_schema(PROJECT_MEMBER_SCHEMA)
+                    .with_attribute(b"role".to_vec(), b"member".to_vec())
+                    .build(),
+                MAX_CREDENTIAL_VALIDITY,
+            )
+            .await
+            .unwrap();
+        let contents = EncodeFormat::Hex
+            .encode_value(&CredentialAndPurposeKeyDisplay(credential))
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials").join("member.cred");
+        write_credential(&path, &contents, false).unwrap();
+
+        // the file contains a credential which can be verified
+        let encoded = hex::decode(std::fs::read_to_string(&path).unwrap()).unwrap();
+        validate_encoded_cred(&encoded, identities, issuer.identifier())
+            .await
+            .unwrap();
+
+        // an existing file is not overwritten without force
+        assert!(write_credential(&path, &contents, false).is_err());
+        assert!(write_credential(&path, &contents, true).is_ok());
+    }
+}
@@ -79,42 +79,40 @@ impl CreateCommand {
                 ))?;
             }
 
-            let vault = vault_state.get().await?;
-
-            let identities_creation = opts
-                .state
-                .get_identities(vault)
-                .await?
-                .identities_creation();
-
             // Create an identity using the KMS key, if provided.
-            let identity = match &self.key_id {
+            // Otherwise, generate the identity keys in the selected vault.
+            let identifier = match &self.key_id {
                 Some(key_id) => {
                     if !vault_state.config().is_aws() {
-                        Err(miette!(
+                        return Err(miette!(
                             "Vault {} is not an AWS KMS vault",
                             self.vault.clone().unwrap_or("default".to_string()),
-                        ))
-                    } else {
-                        let handle = SigningSecretKeyHandle::ECDSASHA256CurveP256(
-                            HandleToSecret::new(key_id.as_bytes().to_vec()),
-                        );
-
-                        Ok(identities_creation
-                            .identity_builder()
-                            .with_existing_key(handle)
-                            .build()
-                            .await?)
+                        )
+                        .into());
                     }
+                    let handle = SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(
+                        key_id.as_bytes().to_vec(),
+                    ));
+                    let identity = opts
+                        .state
+                        .get_identities(vault_state.get().await?)
+                        .await?
+                        .identities_creation()
+                        .identity_builder()
+                        .with_existing_key(handle)
+                        .build()
+                        .await?;
+                    opts.state
+                        .create_identity_state(identity.identifier(), Some(&self.name))
+                        .await?
+                        .identifier()
                 }
-                None => Ok(identities_creation.create_identity().await?),
-            }?;
-
-            opts.state
-                .create_identity_state(identity.identifier(), Some(&self.name))
-                .await?;
-
-            let identifier = identity.identifier().clone();
+                None => opts
+                    .state
+                    .create_identity_with_name_in_vault(&self.name, vault_state.name())
+                    .await?
+                    .identifier(),
+            };
 
             *is_finished.lock().await = true;
             Ok(identifier)
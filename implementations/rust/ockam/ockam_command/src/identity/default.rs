@@ -4,6 +4,7 @@ use clap::Args;
 use colorful::Colorful;
 use miette::miette;
 use ockam_api::cli_state::traits::StateDirTrait;
+use ockam_api::cli_state::CliState;
 
 const LONG_ABOUT: &str = include_str!("./static/default/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/default/after_long_help.txt");
@@ -49,14 +50,51 @@ fn run_impl(opts: CommandGlobalOpts, cmd: DefaultCommand) -> miette::Result<()>
     }
     // No argument provided, show default identity name
     else {
-        let state = opts.state.identities.get_or_default(None)?;
+        let name = default_identity_name(&opts.state)?;
         opts.terminal
             .stdout()
-            .plain(fmt_ok!(
-                "The name of the default identity is '{}'",
-                state.name()
-            ))
+            .plain(fmt_ok!("The name of the default identity is '{}'", &name))
+            .machine(&name)
+            .json(serde_json::json!({ "identity": { "name": &name } }))
             .write_line()?;
         Ok(())
     }
 }
+
+/// Return the name of the default identity
+fn default_identity_name(state: &CliState) -> miette::Result<String> {
+    if !state.identities.default_path()?.exists() {
+        return Err(miette!(
+            "There is no default identity. You can set one with `ockam identity default <name>`"
+        ));
+    }
+    Ok(state.identities.default()?.name().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::tests::identity_config;
+
+    #[test]
+    fn test_default_identity_name() {
+        let state = CliState::test().unwrap();
+        let err = default_identity_name(&state).unwrap_err();
+        assert!(err.to_string().contains("There is no default identity"));
+
+        for (name, identifier) in [
+            ("alice", "Ie92f183eb4c324804ef4d62962dea94cf095a265"),
+            ("bob", "Ifa804b7fca12a19eed206ae180b5b576860ae651"),
+        ] {
+            state
+                .identities
+                .create(name, identity_config(identifier))
+                .unwrap();
+        }
+        // the first identity is the default one
+        assert_eq!(default_identity_name(&state).unwrap(), "alice");
+
+        state.identities.set_default("bob").unwrap();
+        assert_eq!(default_identity_name(&state).unwrap(), "bob");
+    }
+}
@@ -1,15 +1,18 @@
+use crate::util::deletion::DeletionReport;
 use crate::util::node_rpc;
 use crate::{docs, fmt_ok, CommandGlobalOpts};
 use clap::Args;
 use colorful::Colorful;
+use miette::miette;
 
 use ockam::Context;
 use ockam_api::cli_state::traits::StateDirTrait;
+use ockam_api::cli_state::CliState;
 
 const LONG_ABOUT: &str = include_str!("./static/delete/long_about.txt");
 const AFTER_LONG_HELP: &str = include_str!("./static/delete/after_long_help.txt");
 
-/// Delete an identity
+/// Delete identities
 #[derive(Clone, Debug, Args)]
 #[command(
 arg_required_else_help = true,
@@ -18,7 +21,16 @@ after_long_help = docs::after_help(AFTER_LONG_HELP)
 )]
 pub struct DeleteCommand {
     /// Name of the identity to be deleted
-    name: String,
+    #[arg(required_unless_present = "all")]
+    name: Option<String>,
+
+    /// Delete all the identities, except the default one
+    #[arg(long, short, conflicts_with = "name")]
+    all: bool,
+
+    /// Also delete the default identity when used with --all
+    #[arg(long, requires = "all")]
+    include_default: bool,
 
     /// Confirm the deletion without prompting
     #[arg(display_order = 901, long, short)]
@@ -36,21 +48,111 @@ async fn run_impl(
     (opts, cmd): (CommandGlobalOpts, DeleteCommand),
 ) -> miette::Result<()> {
     let state = opts.state;
-    let idt = state.identities.get(&cmd.name)?;
+    if cmd.all {
+        let prompt_msg = if cmd.include_default {
+            "Are you sure you want to delete all identities?"
+        } else {
+            "Are you sure you want to delete all identities, except the default one?"
+        };
+        if opts
+            .terminal
+            .confirmed_with_flag_or_prompt(cmd.yes, prompt_msg)?
+        {
+            let deletion = delete_all_identities(&state, cmd.include_default).await?;
+            deletion.write(&opts, "identity", "identities")?;
+        }
+        return Ok(());
+    }
+
+    let name = cmd
+        .name
+        .ok_or(miette!("An identity name must be provided"))?;
+    state.identities.get(&name)?;
     if opts
         .terminal
         .confirmed_with_flag_or_prompt(cmd.yes, "Are you sure you want to delete this identity?")?
     {
-        state.delete_identity(idt)?;
+        state.delete_identity_by_name(&name).await?;
         opts.terminal
             .stdout()
-            .plain(fmt_ok!(
-                "The identity named '{}' has been deleted",
-                &cmd.name
-            ))
-            .machine(&cmd.name)
-            .json(serde_json::json!({ "name": &cmd.name }))
+            .plain(fmt_ok!("The identity named '{}' has been deleted", &name))
+            .machine(&name)
+            .json(serde_json::json!({ "name": &name }))
             .write_line()?;
     }
     Ok(())
 }
+
+/// Delete all the identities, keeping the default one unless `include_default` is true.
+/// A failure to delete one identity doesn't stop the deletion of the other identities
+async fn delete_all_identities(
+    state: &CliState,
+    include_default: bool,
+) -> miette::Result<DeletionReport<String>> {
+    let default_name = state
+        .identities
+        .default()
+        .ok()
+        .map(|i| i.name().to_string());
+    let mut names: Vec<String> = state
+        .identities
+        .list()?
+        .iter()
+        .map(|i| i.name().to_string())
+        .filter(|name| Some(name) != default_name.as_ref())
+        .collect();
+    // the default identity is deleted last, so that no other identity is promoted as the default
+    if include_default {
+        names.extend(default_name);
+    }
+
+    let mut deletion = DeletionReport::default();
+    for name in names {
+        let result = state.delete_identity_by_name(&name).await;
+        deletion.record(name, result);
+    }
+    Ok(deletion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::tests::identity_config;
+
+    #[tokio::test]
+    async fn test_delete_all_identities_keeps_default() {
+        let state = CliState::test().unwrap();
+        for (name, identifier) in [
+            ("alice", "Ie92f183eb4c324804ef4d62962dea94cf095a265"),
+            ("bob", "Ifa804b7fca12a19eed206ae180b5b576860ae651"),
+            ("carol", "Ie86be15e83d1c93e24dd1967010b01b6df491b45"),
+        ] {
+            state
+                .identities
+                .create(name, identity_config(identifier))
+                .unwrap();
+        }
+        assert!(state.identities.is_default("alice").unwrap());
+
+        let mut deletion = delete_all_identities(&state, false).await.unwrap();
+        deletion.deleted.sort();
+        assert_eq!(
+            deletion.deleted,
+            vec!["bob".to_string(), "carol".to_string()]
+        );
+        assert!(deletion.failed.is_empty());
+        let names: Vec<String> = state
+            .identities
+            .list()
+            .unwrap()
+            .iter()
+            .map(|i| i.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["alice".to_string()]);
+        assert!(state.identities.is_default("alice").unwrap());
+
+        let deletion = delete_all_identities(&state, true).await.unwrap();
+        assert_eq!(deletion.deleted, vec!["alice".to_string()]);
+        assert!(state.identities.list().unwrap().is_empty());
+    }
+}
@@ -40,7 +40,7 @@ impl ListCommand {
         let (opts, _cmd) = options;
         let mut identities: Vec<IdentityListOutput> = Vec::new();
 
-        let idts = opts.state.identities.list()?;
+        let idts = opts.state.identities.list_named_identities()?;
         for identity in idts.iter() {
             let is_finished: Mutex<bool> = Mutex::new(false);
 
@@ -48,7 +48,7 @@ impl ListCommand {
                 let i = IdentityListOutput::new(
                     identity.name().to_string(),
                     identity.identifier().to_string(),
-                    opts.state.identities.default()?.name() == identity.name(),
+                    opts.state.identities.is_default(identity.name())?,
                 );
                 *is_finished.lock().await = true;
                 Ok(i)
@@ -122,3 +122,36 @@ impl Output for IdentityListOutput {
         Ok(output)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_marks_default_identity() {
+        let alice = IdentityListOutput::new(
+            "alice".to_string(),
+            "Ie92f183eb4c324804ef4d62962dea94cf095a265".to_string(),
+            true,
+        );
+        let bob = IdentityListOutput::new(
+            "bob".to_string(),
+            "Ifa804b7fca12a19eed206ae180b5b576860ae651".to_string(),
+            false,
+        );
+
+        let alice_output = alice.output().unwrap();
+        assert!(alice_output.contains("alice"));
+        assert!(alice_output.contains("Ie92f183eb4c324804ef4d62962dea94cf095a265"));
+        assert!(alice_output.contains("(default)"));
+
+        let bob_output = bob.output().unwrap();
+        assert!(bob_output.contains("bob"));
+        assert!(bob_output.contains("Ifa804b7fca12a19eed206ae180b5b576860ae651"));
+        assert!(!bob_output.contains("(default)"));
+
+        let json = json!({ "identities": [alice, bob] });
+        assert_eq!(json["identities"][0]["is_default"], true);
+        assert_eq!(json["identities"][1]["is_default"], false);
+    }
+}
@@ -2,12 +2,14 @@ mod create;
 mod default;
 mod delete;
 mod list;
+mod rename;
 mod show;
 
 use colorful::Colorful;
 pub use create::CreateCommand;
 pub(crate) use delete::DeleteCommand;
 pub(crate) use list::ListCommand;
+pub(crate) use rename::RenameCommand;
 pub(crate) use show::ShowCommand;
 
 use crate::identity::default::DefaultCommand;
@@ -38,6 +40,7 @@ pub enum IdentitySubcommand {
     List(ListCommand),
     Default(DefaultCommand),
     Delete(DeleteCommand),
+    Rename(RenameCommand),
 }
 
 impl IdentityCommand {
@@ -48,6 +51,7 @@ impl IdentityCommand {
             IdentitySubcommand::List(c) => c.run(options),
             IdentitySubcommand::Delete(c) => c.run(options),
             IdentitySubcommand::Default(c) => c.run(options),
+            IdentitySubcommand::Rename(c) => c.run(options),
         }
     }
 }
@@ -109,7 +113,8 @@ pub fn create_default_identity(opts: &CommandGlobalOpts) {
 mod tests {
     use super::*;
     use crate::GlobalArgs;
-    use ockam_api::cli_state::StateItemTrait;
+    use ockam::identity::Identifier;
+    use ockam_api::cli_state::{IdentityConfig, StateItemTrait};
 
     #[test]
     fn test_initialize() {
@@ -133,4 +138,12 @@ mod tests {
         initialize_identity_if_default(&opts, &Some("other".into()));
         assert!(opts.state.identities.default().is_err());
     }
+
+    /// Return the configuration of a non-enrolled identity
+    pub(super) fn identity_config(identifier: &str) -> IdentityConfig {
+        IdentityConfig {
+            identifier: Identifier::try_from(identifier).unwrap(),
+            enrollment_status: None,
+        }
+    }
 }
@@ -0,0 +1,43 @@
+use crate::util::local_cmd;
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+use clap::Args;
+use colorful::Colorful;
+
+const LONG_ABOUT: &str = include_str!("./static/rename/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/rename/after_long_help.txt");
+
+/// Rename an identity
+#[derive(Clone, Debug, Args)]
+#[command(
+arg_required_else_help = true,
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct RenameCommand {
+    /// Current name of the identity
+    old_name: String,
+
+    /// New name of the identity
+    new_name: String,
+}
+
+impl RenameCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        local_cmd(run_impl(options, self));
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: RenameCommand) -> miette::Result<()> {
+    opts.state.rename_identity(&cmd.old_name, &cmd.new_name)?;
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "The identity named '{}' has been renamed to '{}'",
+            &cmd.old_name,
+            &cmd.new_name
+        ))
+        .machine(&cmd.new_name)
+        .json(serde_json::json!({ "old_name": &cmd.old_name, "new_name": &cmd.new_name }))
+        .write_line()?;
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt::Display;
 
 use crate::identity::{get_identity_name, initialize_identity_if_default};
@@ -7,7 +8,7 @@ use crate::{docs, CommandGlobalOpts};
 use clap::Args;
 use miette::IntoDiagnostic;
 use ockam::identity::verified_change::VerifiedChange;
-use ockam::identity::{Identifier, Identity, Vault};
+use ockam::identity::{AttributesEntry, Identifier, Identity, TimestampInSeconds};
 use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
 use ockam_node::Context;
 use serde::Serialize;
@@ -38,6 +39,10 @@ pub struct ShowCommand {
     //      for `full` (change history) identity.
     #[arg(long, value_enum, requires = "full")]
     encoding: Option<EncodeFormat>,
+
+    /// Show the attributes attested for the identity
+    #[arg(long, conflicts_with = "full")]
+    attributes: bool,
 }
 
 impl ShowCommand {
@@ -54,30 +59,26 @@ impl ShowCommand {
         let name = get_identity_name(&opts.state, &cmd.name);
         let state = opts.state.identities.get(&name)?;
         let identifier = state.config().identifier();
-        let (plain, json) = if cmd.full {
-            let change_history = opts
+        let (plain, json) = if cmd.attributes {
+            let entry = opts
                 .state
                 .identities
                 .identities_repository()
                 .await?
-                .get_identity(&identifier)
+                .as_attributes_reader()
+                .get_attributes(&identifier)
                 .await
                 .into_diagnostic()?;
-
+            let attributes = ShowAttributes::new(identifier, entry);
+            (attributes.to_string(), to_string_pretty(&attributes))
+        } else if cmd.full {
+            let named_identity = opts.state.get_named_identity(Some(&name)).await?;
             if Some(EncodeFormat::Hex) == cmd.encoding {
-                let encoded = hex::encode(change_history.export().into_diagnostic()?);
+                let encoded = hex::encode(named_identity.identity().export().into_diagnostic()?);
                 let json = to_string_pretty(&json!({"encoded": &encoded}));
                 (encoded, json)
             } else {
-                let identity: ShowIdentity = Identity::import_from_change_history(
-                    Some(&identifier),
-                    change_history,
-                    Vault::create_verifying_vault(),
-                )
-                .await
-                .into_diagnostic()?
-                .into();
-
+                let identity: ShowIdentity = named_identity.identity().clone().into();
                 (identity.to_string(), to_string_pretty(&identity))
             }
         } else {
@@ -141,6 +142,128 @@ impl Output for ShowIdentity {
     }
 }
 
+/// Attributes of an identity.
+///
+/// In JSON, attribute values which are valid UTF-8 are serialized as strings and
+/// other values are serialized as `{"hex": "<hex-encoded bytes>"}`.
+/// Attributes which names are not valid UTF-8 are kept in a separate map, indexed by their
+/// hex-encoded name, so that they can't be mistaken for an attribute with a UTF-8 name.
+#[derive(Serialize)]
+struct ShowAttributes {
+    identifier: Identifier,
+    attributes: BTreeMap<String, AttributeValue>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    hex_named_attributes: BTreeMap<String, AttributeValue>,
+    added: Option<TimestampInSeconds>,
+    expires: Option<TimestampInSeconds>,
+    attested_by: Option<Identifier>,
+}
+
+impl ShowAttributes {
+    fn new(identifier: Identifier, entry: Option<AttributesEntry>) -> Self {
+        match entry {
+            Some(entry) => {
+                let mut attributes = BTreeMap::new();
+                let mut hex_named_attributes = BTreeMap::new();
+                for (name, value) in entry.attrs() {
+                    match std::str::from_utf8(name) {
+                        Ok(name) => {
+                            let value = match entry.get_str(name) {
+                                Some(text) => AttributeValue::Text(text.into_owned()),
+                                None => AttributeValue::binary(value),
+                            };
+                            attributes.insert(name.to_string(), value);
+                        }
+                        Err(_) => {
+                            hex_named_attributes
+                                .insert(hex::encode(name), AttributeValue::new(value));
+                        }
+                    }
+                }
+                Self {
+                    identifier,
+                    attributes,
+                    hex_named_attributes,
+                    added: Some(entry.added()),
+                    expires: entry.expires(),
+                    attested_by: entry.attested_by(),
+                }
+            }
+            None => Self {
+                identifier,
+                attributes: BTreeMap::new(),
+                hex_named_attributes: BTreeMap::new(),
+                added: None,
+                expires: None,
+                attested_by: None,
+            },
+        }
+    }
+}
+
+impl Display for ShowAttributes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Identifier: {}", self.identifier)?;
+        if self.attributes.is_empty() && self.hex_named_attributes.is_empty() {
+            return writeln!(f, "  No attributes");
+        }
+        writeln!(f, "  Attributes:")?;
+        for (name, value) in self.attributes.iter() {
+            writeln!(f, "    {name}: {value}")?;
+        }
+        for (name, value) in self.hex_named_attributes.iter() {
+            writeln!(f, "    (hex) {name}: {value}")?;
+        }
+        if let Some(added) = self.added {
+            writeln!(f, "  Added:       {}", *added)?;
+        }
+        if let Some(expires) = self.expires {
+            writeln!(f, "  Expires:     {}", *expires)?;
+        }
+        if let Some(attested_by) = &self.attested_by {
+            writeln!(f, "  Attested by: {attested_by}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Output for ShowAttributes {
+    fn output(&self) -> crate::error::Result<String> {
+        Ok(self.to_string())
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(untagged)]
+enum AttributeValue {
+    Text(String),
+    Binary { hex: String },
+}
+
+impl AttributeValue {
+    fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(s) => AttributeValue::Text(s.to_string()),
+            Err(_) => AttributeValue::binary(bytes),
+        }
+    }
+
+    fn binary(bytes: &[u8]) -> Self {
+        AttributeValue::Binary {
+            hex: hex::encode(bytes),
+        }
+    }
+}
+
+impl Display for AttributeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeValue::Text(s) => write!(f, "{s}"),
+            AttributeValue::Binary { hex } => write!(f, "{hex}"),
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct Change {
     pub identifier: String,
@@ -157,3 +280,70 @@ impl From<VerifiedChange> for Change {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_show_attributes() {
+        let identifier = Identifier::try_from("Ie92f183eb4c324804ef4d62962dea94cf095a265").unwrap();
+        let authority = Identifier::try_from("Ifa804b7fca12a19eed206ae180b5b576860ae651").unwrap();
+        let mut attrs = BTreeMap::new();
+        attrs.insert(b"role".to_vec(), b"member".to_vec());
+        attrs.insert(b"key".to_vec(), vec![0xff, 0x01]);
+        let entry = AttributesEntry::new(
+            attrs,
+            TimestampInSeconds(1000),
+            Some(TimestampInSeconds(2000)),
+            Some(authority.clone()),
+        );
+
+        let output = ShowAttributes::new(identifier, Some(entry))
+            .output()
+            .unwrap();
+        assert!(output.contains("role: member"));
+        assert!(output.contains("key: ff01"));
+        assert!(output.contains("Added:       1000"));
+        assert!(output.contains("Expires:     2000"));
+        assert!(output.contains(&format!("Attested by: {authority}")));
+    }
+
+    #[test]
+    fn test_show_attributes_json() {
+        let identifier = Identifier::try_from("Ie92f183eb4c324804ef4d62962dea94cf095a265").unwrap();
+        let authority = Identifier::try_from("Ifa804b7fca12a19eed206ae180b5b576860ae651").unwrap();
+        let mut attrs = BTreeMap::new();
+        attrs.insert(b"role".to_vec(), b"member".to_vec());
+        attrs.insert(b"key".to_vec(), vec![0xff, 0x01]);
+        // a UTF-8 name which is also the hex encoding of a non UTF-8 name
+        attrs.insert(b"fe".to_vec(), b"text".to_vec());
+        attrs.insert(vec![0xfe], b"binary name".to_vec());
+        let entry = AttributesEntry::new(
+            attrs,
+            TimestampInSeconds(1000),
+            Some(TimestampInSeconds(2000)),
+            Some(authority.clone()),
+        );
+
+        let attributes = ShowAttributes::new(identifier.clone(), Some(entry));
+        let json = serde_json::to_value(&attributes).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "identifier": identifier.to_string(),
+                "attributes": {
+                    "role": "member",
+                    "key": {"hex": "ff01"},
+                    "fe": "text"
+                },
+                "hex_named_attributes": {
+                    "fe": "binary name"
+                },
+                "added": 1000,
+                "expires": 2000,
+                "attested_by": authority.to_string(),
+            })
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use std::env::current_exe;
 use std::sync::Arc;
 use std::{path::PathBuf, process, str::FromStr};
 
@@ -29,7 +30,9 @@ use ockam_api::{
 use ockam_core::api::{Request, ResponseHeader, Status};
 use ockam_core::{route, LOCAL};
 
-use crate::node::util::{spawn_node, NodeManagerDefaults};
+use crate::node::util::{
+    spawn_node, wait_for_node_ready, NodeManagerDefaults, DEFAULT_NODE_READY_TIMEOUT,
+};
 use crate::secure_channel::listener::create as secure_channel_listener;
 use crate::service::config::Config;
 use crate::terminal::OckamColor;
@@ -238,6 +241,8 @@ pub(crate) async fn background_mode(
                     "ockam node show".color(OckamColor::PrimaryResource.color())
                 ),
         )
+        .machine(node_name)
+        .json(serde_json::json!({ "node": { "name": node_name } }))
         .write_line()?;
 
     Ok(())
@@ -290,10 +295,15 @@ async fn run_foreground_node(
 
     let node_state = opts.state.nodes.get(&node_name)?;
     node_state.set_pid(process::id() as i32)?;
+    let mut setup = node_state.config().setup_mut();
+    // Keep the executable which created the node when the node is restarted
+    if setup.ockam_exe.is_none() {
+        if let Ok(ockam_exe) = current_exe() {
+            setup = setup.set_ockam_exe(ockam_exe);
+        }
+    }
     node_state.set_setup(
-        &node_state
-            .config()
-            .setup_mut()
+        &setup
             .set_verbose(opts.global_args.verbose)
             .set_api_transport(
                 CreateTransportJson::new(
@@ -348,6 +358,9 @@ async fn run_foreground_node(
         }
     }
 
+    // Let the process which spawned this node know that it can be queried
+    node_state.set_ready()?;
+
     // Create a channel for communicating back to the main thread
     let (tx, mut rx) = tokio::sync::mpsc::channel(2);
     shutdown::wait(
@@ -479,5 +492,8 @@ pub async fn spawn_background_node(
         cmd.logging_to_file(),
     )?;
 
+    let node_state = opts.state.nodes.get(&node_name)?;
+    wait_for_node_ready(&node_state, DEFAULT_NODE_READY_TIMEOUT).await?;
+
     Ok(())
 }
@@ -52,11 +52,8 @@ fn run_impl(opts: CommandGlobalOpts, cmd: DeleteCommand) -> miette::Result<()> {
         .confirmed_with_flag_or_prompt(cmd.yes, prompt_msg)?
     {
         if cmd.all {
-            delete_all_nodes(&opts, cmd.force)?;
-            opts.terminal
-                .stdout()
-                .plain(fmt_ok!("All nodes have been deleted"))
-                .write_line()?;
+            let deletion = delete_all_nodes(&opts.state, cmd.force)?;
+            deletion.write(&opts, "node", "nodes")?;
         } else {
             delete_node(&opts, &node_name, cmd.force)?;
             opts.terminal
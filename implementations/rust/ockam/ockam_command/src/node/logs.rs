@@ -1,8 +1,11 @@
+use std::path::Path;
+
 use crate::node::get_node_name;
 use crate::util::local_cmd;
 use crate::{docs, CommandGlobalOpts};
 use clap::Args;
-use ockam_api::cli_state::StateDirTrait;
+use miette::IntoDiagnostic;
+use ockam_api::cli_state::{NodeState, StateDirTrait};
 
 const LONG_ABOUT: &str = include_str!("./static/logs/long_about.txt");
 const PREVIEW_TAG: &str = include_str!("../static/preview_tag.txt");
@@ -20,8 +23,12 @@ pub struct LogCommand {
     node_name: Option<String>,
 
     /// Show the standard error log file.
-    #[arg(long = "err")]
+    #[arg(long = "err", visible_alias = "stderr")]
     show_err: bool,
+
+    /// Show the contents of both log files, interleaved by the timestamps of their lines.
+    #[arg(long, conflicts_with = "show_err")]
+    combined: bool,
 }
 
 impl LogCommand {
@@ -33,14 +40,141 @@ impl LogCommand {
 fn run_impl(opts: CommandGlobalOpts, cmd: LogCommand) -> miette::Result<()> {
     let node_name = get_node_name(&opts.state, &cmd.node_name);
     let node_state = opts.state.nodes.get(node_name)?;
+    let output = log_output(&node_state, &cmd)?;
+    opts.terminal.stdout().machine(output).write_line()?;
+    Ok(())
+}
+
+/// Return either the path of a log file or the combined contents of both log files
+fn log_output(node_state: &NodeState, cmd: &LogCommand) -> miette::Result<String> {
+    if cmd.combined {
+        let stdout = read_log(&node_state.stdout_log())?;
+        let stderr = read_log(&node_state.stderr_log())?;
+        return Ok(combine_logs(&stdout, &stderr).join("\n"));
+    }
     let log_file_path = if cmd.show_err {
         node_state.stderr_log()
     } else {
         node_state.stdout_log()
     };
-    opts.terminal
-        .stdout()
-        .machine(log_file_path.display().to_string())
-        .write_line()?;
-    Ok(())
+    Ok(log_file_path.display().to_string())
+}
+
+/// A log file is only created once something is logged
+fn read_log(path: &Path) -> miette::Result<String> {
+    match std::fs::read_to_string(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        r => r.into_diagnostic(),
+    }
+}
+
+/// Interleave the lines of two logs by timestamp.
+/// A line without a timestamp, like the continuation of a multi-line message,
+/// stays after the line preceding it in its own log.
+fn combine_logs<'a>(first: &'a str, second: &'a str) -> Vec<&'a str> {
+    let first = timestamped_lines(first);
+    let second = timestamped_lines(second);
+    let mut combined = Vec::with_capacity(first.len() + second.len());
+    let (mut i, mut j) = (0, 0);
+    while i < first.len() && j < second.len() {
+        if first[i].0 <= second[j].0 {
+            combined.push(first[i].1);
+            i += 1;
+        } else {
+            combined.push(second[j].1);
+            j += 1;
+        }
+    }
+    combined.extend(first[i..].iter().map(|(_, l)| *l));
+    combined.extend(second[j..].iter().map(|(_, l)| *l));
+    combined
+}
+
+/// Associate each line with its timestamp, or with the timestamp of the previous line
+fn timestamped_lines(log: &str) -> Vec<(&str, &str)> {
+    let mut timestamp = "";
+    log.lines()
+        .map(|line| {
+            if let Some(t) = line_timestamp(line) {
+                timestamp = t;
+            }
+            (timestamp, line)
+        })
+        .collect()
+}
+
+/// Log lines start with an RFC 3339 UTC timestamp, e.g. `2023-10-16T12:00:00.123456Z`,
+/// which can be compared as a string
+fn line_timestamp(line: &str) -> Option<&str> {
+    let token = line.split_whitespace().next()?;
+    let bytes = token.as_bytes();
+    let is_timestamp = bytes.len() >= 20
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[10] == b'T'
+        && token.ends_with('Z');
+    is_timestamp.then_some(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_api::cli_state::{init_node_state, CliState};
+
+    fn log_command(show_err: bool, combined: bool) -> LogCommand {
+        LogCommand {
+            node_name: None,
+            show_err,
+            combined,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_log_output() {
+        let state = CliState::test().unwrap();
+        init_node_state(&state, "n1", None, None).await.unwrap();
+        let node_state = state.nodes.get("n1").unwrap();
+        std::fs::write(
+            node_state.stdout_log(),
+            "2023-10-16T12:00:00.000001Z  INFO started\n2023-10-16T12:00:02.000000Z  INFO listening\n",
+        )
+        .unwrap();
+        std::fs::write(
+            node_state.stderr_log(),
+            "2023-10-16T12:00:01.000000Z ERROR failed\n  caused by: timeout\n",
+        )
+        .unwrap();
+
+        let stdout = log_output(&node_state, &log_command(false, false)).unwrap();
+        assert_eq!(stdout, node_state.stdout_log().display().to_string());
+
+        let stderr = log_output(&node_state, &log_command(true, false)).unwrap();
+        assert_eq!(stderr, node_state.stderr_log().display().to_string());
+
+        let combined = log_output(&node_state, &log_command(false, true)).unwrap();
+        assert_eq!(
+            combined.lines().collect::<Vec<_>>(),
+            vec![
+                "2023-10-16T12:00:00.000001Z  INFO started",
+                "2023-10-16T12:00:01.000000Z ERROR failed",
+                "  caused by: timeout",
+                "2023-10-16T12:00:02.000000Z  INFO listening",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_combined_logs_without_stderr() {
+        let state = CliState::test().unwrap();
+        init_node_state(&state, "n1", None, None).await.unwrap();
+        let node_state = state.nodes.get("n1").unwrap();
+        std::fs::write(
+            node_state.stdout_log(),
+            "2023-10-16T12:00:00.000001Z  INFO started\n",
+        )
+        .unwrap();
+
+        let combined = log_output(&node_state, &log_command(false, true)).unwrap();
+        assert_eq!(combined, "2023-10-16T12:00:00.000001Z  INFO started");
+    }
 }
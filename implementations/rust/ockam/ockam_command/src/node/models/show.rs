@@ -8,6 +8,7 @@ use ockam_multiaddr::{
 };
 use serde::Serialize;
 
+use crate::node::util::NodeLiveness;
 use crate::output::Output;
 
 use super::{
@@ -23,6 +24,8 @@ pub struct ShowNodeResponse {
     pub is_default: bool,
     pub name: String,
     pub is_up: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liveness: Option<NodeLiveness>,
     pub route: RouteToNode,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub identity: Option<String>,
@@ -64,6 +67,7 @@ impl ShowNodeResponse {
             is_default,
             name: name.to_owned(),
             is_up,
+            liveness: None,
             route: RouteToNode { short, verbose },
             identity: None,
             transports: Default::default(),
@@ -85,14 +89,13 @@ impl Display for ShowNodeResponse {
             writeln!(buffer, "  Name: {}", self.name)?;
         }
 
-        writeln!(
-            buffer,
-            "  Status: {}",
-            match self.is_up {
-                true => "UP".light_green(),
-                false => "DOWN".light_red(),
+        match (self.is_up, self.liveness) {
+            (true, _) => writeln!(buffer, "  Status: {}", "UP".light_green())?,
+            (false, Some(liveness)) => {
+                writeln!(buffer, "  Status: {} ({})", "DOWN".light_red(), liveness)?
             }
-        )?;
+            (false, None) => writeln!(buffer, "  Status: {}", "DOWN".light_red())?,
+        }
 
         writeln!(buffer, "  Route To Node:")?;
         if let Some(short) = &self.route.short {
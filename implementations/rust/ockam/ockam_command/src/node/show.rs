@@ -12,7 +12,7 @@ use ockam_api::cli_state::{CliState, StateDirTrait, StateItemTrait};
 use ockam_api::nodes::models::portal::{InletList, OutletList};
 
 use crate::node::get_node_name;
-use crate::node::util::check_default;
+use crate::node::util::{check_default, get_node_liveness, DEFAULT_NODE_STATUS_TIMEOUT};
 use crate::util::{api, node_rpc};
 use crate::{docs, CommandGlobalOpts, Result};
 
@@ -81,9 +81,16 @@ pub async fn print_query_status(
 
             // it is expected to not be able to open an arbitrary TCP connection on an authority node
             // so in that case we display an UP status
-            let is_authority_node = node_state.config().setup().authority_node.unwrap_or(false);
-
-            ShowNodeResponse::new(is_default, node_name, is_authority_node, node_port)
+            let is_authority_node = node_state.config().setup().is_authority_node();
+
+            let mut node_info =
+                ShowNodeResponse::new(is_default, node_name, is_authority_node, node_port);
+            if !is_authority_node {
+                // distinguish a node which is stopped from a node which is too slow to answer
+                node_info.liveness =
+                    Some(get_node_liveness(ctx, node, DEFAULT_NODE_STATUS_TIMEOUT).await);
+            }
+            node_info
         } else {
             let node_state = cli_state.nodes.get(node_name)?;
             let node_port = node_state
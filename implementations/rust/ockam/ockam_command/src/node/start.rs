@@ -1,12 +1,15 @@
 use clap::Args;
 use colorful::Colorful;
+use miette::miette;
 
-use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
+use ockam_api::cli_state::{NodeState, StateDirTrait, StateItemTrait};
 use ockam_api::nodes::BackgroundNode;
 use ockam_node::Context;
 
 use crate::node::show::print_query_status;
-use crate::node::util::{check_default, spawn_node};
+use crate::node::util::{
+    check_default, spawn_node, wait_for_node_ready, DEFAULT_NODE_READY_TIMEOUT,
+};
 use crate::node::{get_node_name, initialize_node_if_default};
 use crate::util::node_rpc;
 use crate::{docs, fmt_err, CommandGlobalOpts};
@@ -27,6 +30,7 @@ pub struct StartCommand {
     /// Name of the node to be started
     node_name: Option<String>,
 
+    /// Check that the node identity keys are stored in an AWS KMS vault before starting it
     #[arg(long, default_value = "false")]
     aws_kms: bool,
 }
@@ -56,6 +60,7 @@ async fn run_impl(
             .write_line()?;
         return Ok(());
     }
+    check_aws_kms_vault(&node_state, cmd.aws_kms)?;
     node_state.kill_process(false)?;
     let node_setup = node_state.config().setup();
     opts.global_args.verbose = node_setup.verbose;
@@ -77,6 +82,8 @@ async fn run_impl(
         true,                                          // Restarted nodes will log to files
     )?;
 
+    wait_for_node_ready(&node_state, DEFAULT_NODE_READY_TIMEOUT).await?;
+
     // Print node status
     let mut node = BackgroundNode::create(&ctx, &opts.state, &node_name).await?;
     let is_default = check_default(&opts, &node_name);
@@ -84,3 +91,31 @@ async fn run_impl(
 
     Ok(())
 }
+
+/// A node keeps the vault it was created with, so when `--aws-kms` is used
+/// we can only check that this vault is an AWS KMS vault
+fn check_aws_kms_vault(node_state: &NodeState, aws_kms: bool) -> miette::Result<()> {
+    if aws_kms && !node_state.config().vault_state()?.config().is_aws() {
+        return Err(miette!(
+            "The node '{}' does not use an AWS KMS vault. Create a vault with `ockam vault create --aws-kms` and use it to create the node with `ockam node create --vault <VAULT_NAME>`",
+            node_state.name()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_api::cli_state::{init_node_state, CliState};
+
+    #[tokio::test]
+    async fn test_check_aws_kms_vault() {
+        let state = CliState::test().unwrap();
+        init_node_state(&state, "n1", None, None).await.unwrap();
+        let node_state = state.nodes.get("n1").unwrap();
+
+        assert!(check_aws_kms_vault(&node_state, false).is_ok());
+        assert!(check_aws_kms_vault(&node_state, true).is_err());
+    }
+}
@@ -1,16 +1,25 @@
 use std::env::current_exe;
+use std::ffi::OsStr;
+use std::fmt::{Display, Formatter};
 use std::fs::OpenOptions;
+use std::future::Future;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 
 use miette::Context as _;
 use miette::{miette, IntoDiagnostic};
 use rand::random;
 
-use ockam_api::cli_state::StateDirTrait;
+use ockam_api::cli_state::{CliState, NodeState, StateDirTrait};
+use ockam_api::nodes::models::base::NodeStatus;
+use ockam_api::nodes::BackgroundNode;
 use ockam_core::env::get_env_with_default;
+use ockam_node::Context;
 
+use crate::util::api;
 use crate::util::api::TrustContextOpts;
+use crate::util::deletion::DeletionReport;
 use crate::CommandGlobalOpts;
 
 pub struct NodeManagerDefaults {
@@ -34,21 +43,19 @@ pub fn delete_node(opts: &CommandGlobalOpts, name: &str, force: bool) -> miette:
     Ok(())
 }
 
-pub fn delete_all_nodes(opts: &CommandGlobalOpts, force: bool) -> miette::Result<()> {
-    let nodes_states = opts.state.nodes.list()?;
-    let mut deletion_errors = Vec::new();
-    for s in nodes_states {
-        if let Err(e) = opts.state.nodes.delete_sigkill(s.name(), force) {
-            deletion_errors.push((s.name().to_string(), e));
-        }
-    }
-    if !deletion_errors.is_empty() {
-        return Err(miette!(
-            "errors while deleting nodes: {:?}",
-            deletion_errors
-        ));
+/// Delete all the nodes.
+/// A failure to delete one node doesn't stop the deletion of the other nodes
+pub fn delete_all_nodes(
+    cli_state: &CliState,
+    force: bool,
+) -> miette::Result<DeletionReport<String>> {
+    let mut deletion = DeletionReport::default();
+    for s in cli_state.nodes.list()? {
+        let name = s.name().to_string();
+        let result = cli_state.nodes.delete_sigkill(&name, force);
+        deletion.record(name, result);
     }
-    Ok(())
+    Ok(deletion)
 }
 
 pub fn check_default(opts: &CommandGlobalOpts, name: &str) -> bool {
@@ -161,14 +168,13 @@ pub fn run_ockam(
     args: Vec<String>,
     logging_to_file: bool,
 ) -> miette::Result<()> {
-    // On systems with non-obvious path setups (or during
-    // development) re-executing the current binary is a more
-    // deterministic way of starting a node.
-    let ockam_exe = get_env_with_default("OCKAM", current_exe().unwrap_or_else(|_| "ockam".into()))
-        .into_diagnostic()?;
     let node_state = opts.state.nodes.get(node_name)?;
+    let ockam_exe = get_ockam_exe(&node_state)?;
 
-    let mut cmd = Command::new(ockam_exe);
+    // Make sure that a marker left by a previous run is not mistaken for this run's readiness
+    node_state.clear_ready()?;
+
+    let mut cmd = ockam_command(ockam_exe, &opts.state);
 
     if logging_to_file {
         let (mlog, elog) = { (node_state.stdout_log(), node_state.stderr_log()) };
@@ -198,3 +204,259 @@ pub fn run_ockam(
 
     Ok(())
 }
+
+/// Return the executable to use in order to run a node:
+///
+///  - the executable which created the node, if it still exists, so that restarts are reproducible
+///  - otherwise the current binary
+fn get_ockam_exe(node_state: &NodeState) -> miette::Result<PathBuf> {
+    if let Some(ockam_exe) = &node_state.config().setup().ockam_exe {
+        if ockam_exe.exists() {
+            return Ok(ockam_exe.clone());
+        }
+    }
+    // On systems with non-obvious path setups (or during
+    // development) re-executing the current binary is a more
+    // deterministic way of starting a node.
+    get_env_with_default("OCKAM", current_exe().unwrap_or_else(|_| "ockam".into()))
+        .into_diagnostic()
+}
+
+/// Environment variables configuring the logs, which are passed on to child processes
+const LOG_ENV_VARS: [&str; 4] = [
+    "OCKAM_LOG",
+    "OCKAM_LOG_MAX_SIZE_MB",
+    "OCKAM_LOG_MAX_FILES",
+    "OCKAM_LOG_FORMAT",
+];
+
+/// Create a command running the ockam executable with the same state directory
+/// and logging configuration as the current process
+fn ockam_command(ockam_exe: impl AsRef<OsStr>, cli_state: &CliState) -> Command {
+    let mut cmd = Command::new(ockam_exe);
+    cmd.env("OCKAM_HOME", &cli_state.dir);
+    for var in LOG_ENV_VARS {
+        if let Some(value) = std::env::var_os(var) {
+            cmd.env(var, value);
+        }
+    }
+    cmd
+}
+
+/// Default time given to a spawned node to bind its API listener
+pub const DEFAULT_NODE_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+const NODE_READY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Wait until a node marks itself as ready, or fail after `timeout`
+pub async fn wait_for_node_ready(node_state: &NodeState, timeout: Duration) -> miette::Result<()> {
+    let start = Instant::now();
+    while !node_state.is_ready() {
+        if start.elapsed() >= timeout {
+            return Err(miette!(
+                "The node '{}' was not ready after {:?}",
+                node_state.name(),
+                timeout
+            ));
+        }
+        tokio::time::sleep(NODE_READY_POLL_INTERVAL).await;
+    }
+    Ok(())
+}
+
+/// Default time given to a node to answer a status request
+pub const DEFAULT_NODE_STATUS_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Liveness of a node, as determined by a status request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum NodeLiveness {
+    /// The node answered and reported that it is running
+    Running,
+    /// The node answered but did not report that it is running
+    Degraded,
+    /// The node did not answer before the timeout
+    Unresponsive,
+    /// The node could not be reached
+    Stopped,
+}
+
+impl NodeLiveness {
+    pub fn is_running(&self) -> bool {
+        matches!(self, NodeLiveness::Running)
+    }
+}
+
+impl Display for NodeLiveness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeLiveness::Running => write!(f, "Running"),
+            NodeLiveness::Degraded => write!(f, "Degraded"),
+            NodeLiveness::Unresponsive => write!(f, "Unresponsive (timeout)"),
+            NodeLiveness::Stopped => write!(f, "Stopped"),
+        }
+    }
+}
+
+/// Send a status request to a node and classify its liveness
+pub async fn get_node_liveness(
+    ctx: &Context,
+    node: &BackgroundNode,
+    timeout: Duration,
+) -> NodeLiveness {
+    classify_node_status(
+        node.ask_with_timeout(ctx, api::query_status(), timeout),
+        timeout,
+    )
+    .await
+}
+
+/// Classify the result of a status request:
+///
+///  - a request which does not complete within `timeout` means that the node is unresponsive
+///  - a request which fails before `timeout` means that the node is stopped
+///  - a node replying with a status other than "Running" is degraded
+async fn classify_node_status<F>(query: F, timeout: Duration) -> NodeLiveness
+where
+    F: Future<Output = miette::Result<NodeStatus>>,
+{
+    let start = Instant::now();
+    match tokio::time::timeout(timeout, query).await {
+        Ok(Ok(status)) if status.status.eq_ignore_ascii_case("running") => NodeLiveness::Running,
+        Ok(Ok(_)) => NodeLiveness::Degraded,
+        // the request client has its own timeout, which may expire first
+        Ok(Err(_)) if start.elapsed() < timeout => NodeLiveness::Stopped,
+        _ => NodeLiveness::Unresponsive,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_api::cli_state::{init_node_state, CliState};
+
+    fn node_status(status: &str) -> NodeStatus {
+        NodeStatus::new("node", status, 1, 1)
+    }
+
+    #[tokio::test]
+    async fn test_classify_node_status() {
+        let timeout = Duration::from_millis(100);
+
+        let running = async { Ok(node_status("Running")) };
+        assert_eq!(
+            classify_node_status(running, timeout).await,
+            NodeLiveness::Running
+        );
+
+        let degraded = async { Ok(node_status("Starting")) };
+        assert_eq!(
+            classify_node_status(degraded, timeout).await,
+            NodeLiveness::Degraded
+        );
+
+        let stopped = async { Err(miette!("connection refused")) };
+        assert_eq!(
+            classify_node_status(stopped, timeout).await,
+            NodeLiveness::Stopped
+        );
+
+        let unresponsive = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(node_status("Running"))
+        };
+        assert_eq!(
+            classify_node_status(unresponsive, timeout).await,
+            NodeLiveness::Unresponsive
+        );
+    }
+
+    #[test]
+    fn test_ockam_command_uses_the_state_directory() {
+        let state = CliState::test().unwrap();
+        let cmd = ockam_command("ockam", &state);
+        let ockam_home = cmd
+            .get_envs()
+            .find(|(k, _)| *k == "OCKAM_HOME")
+            .and_then(|(_, v)| v);
+        assert_eq!(ockam_home, Some(state.dir.as_os_str()));
+    }
+
+    #[tokio::test]
+    async fn test_get_ockam_exe() {
+        let state = CliState::test().unwrap();
+        init_node_state(&state, "n1", None, None).await.unwrap();
+        let node_state = state.nodes.get("n1").unwrap();
+
+        // no stored executable: use the current binary
+        let current = get_ockam_exe(&node_state).unwrap();
+
+        // the stored executable is used when present
+        let dir = tempfile::tempdir().unwrap();
+        let ockam_exe = dir.path().join("ockam");
+        std::fs::write(&ockam_exe, "").unwrap();
+        node_state
+            .set_setup(
+                &node_state
+                    .config()
+                    .setup_mut()
+                    .set_ockam_exe(ockam_exe.clone()),
+            )
+            .unwrap();
+        let node_state = state.nodes.get("n1").unwrap();
+        assert_eq!(get_ockam_exe(&node_state).unwrap(), ockam_exe);
+
+        // fall back to the current binary when the stored one was removed
+        std::fs::remove_file(&ockam_exe).unwrap();
+        assert_eq!(get_ockam_exe(&node_state).unwrap(), current);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_node_ready() {
+        let state = CliState::test().unwrap();
+        init_node_state(&state, "n1", None, None).await.unwrap();
+        let node_state = state.nodes.get("n1").unwrap();
+
+        // no marker: the waiter times out
+        assert!(wait_for_node_ready(&node_state, Duration::from_millis(100))
+            .await
+            .is_err());
+
+        // the node becomes ready after a delay: the waiter blocks until then
+        let delay = Duration::from_millis(300);
+        let delayed_node = node_state.clone();
+        let ready = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            delayed_node.set_ready().unwrap();
+        });
+        let start = Instant::now();
+        wait_for_node_ready(&node_state, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(start.elapsed() >= delay);
+        ready.await.unwrap();
+
+        // stopping the node removes the marker
+        node_state.kill_process(false).unwrap();
+        assert!(!node_state.is_ready());
+    }
+
+    #[tokio::test]
+    async fn test_delete_all_nodes_reports_failures() {
+        let state = CliState::test().unwrap();
+        init_node_state(&state, "n1", None, None).await.unwrap();
+        init_node_state(&state, "n2", None, None).await.unwrap();
+
+        // a corrupted pid file makes the deletion of n2 fail
+        std::fs::write(state.nodes.path("n2").join("pid"), "not a pid").unwrap();
+
+        let deletion = delete_all_nodes(&state, false).unwrap();
+        assert_eq!(deletion.deleted, vec!["n1".to_string()]);
+        assert_eq!(deletion.failed.len(), 1);
+        assert_eq!(deletion.failed[0].name, "n2");
+        assert!(!deletion.failed[0].error.is_empty());
+
+        // n1 is deleted even though n2 failed
+        assert!(!state.nodes.exists("n1"));
+        assert!(state.nodes.exists("n2"));
+    }
+}
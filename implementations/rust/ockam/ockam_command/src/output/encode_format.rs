@@ -20,15 +20,22 @@ impl EncodeFormat {
     where
         T: Encode<()> + Output,
     {
-        let o = match self {
+        let o = self.encode_value(e)?;
+        print!("{o}");
+        Ok(())
+    }
+
+    /// Encode a value as a string
+    pub fn encode_value<T>(&self, e: &T) -> Result<String>
+    where
+        T: Encode<()> + Output,
+    {
+        Ok(match self {
             EncodeFormat::Plain => e.output().wrap_err("Failed serialize output")?,
             EncodeFormat::Hex => {
                 let bytes = minicbor::to_vec(e).expect("Unable to encode response");
                 hex::encode(bytes)
             }
-        };
-
-        print!("{o}");
-        Ok(())
+        })
     }
 }
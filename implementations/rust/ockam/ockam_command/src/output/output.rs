@@ -14,7 +14,7 @@ use ockam::identity::models::{
 use ockam::identity::{Credential, Identifier, Identity, TimestampInSeconds};
 use serde::{Serialize, Serializer};
 
-use ockam_api::cli_state::{ProjectConfigCompact, StateItemTrait, VaultState};
+use ockam_api::cli_state::{ProjectConfigCompact, VaultState};
 use ockam_api::cloud::project::Project;
 use ockam_api::cloud::space::Space;
 use ockam_api::nodes::models::portal::{InletStatus, OutletStatus};
@@ -365,14 +365,7 @@ impl Output for VaultState {
     fn output(&self) -> Result<String> {
         let mut output = String::new();
         writeln!(output, "Name: {}", self.name())?;
-        writeln!(
-            output,
-            "Type: {}",
-            match self.config().is_aws() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-        )?;
+        writeln!(output, "Type: {}", self.kind())?;
         Ok(output)
     }
 
@@ -386,12 +379,9 @@ impl Output for VaultState {
         write!(
             output,
             "Type {}",
-            match self.config().is_aws() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-            .to_string()
-            .color(OckamColor::PrimaryResource.color())
+            self.kind()
+                .to_string()
+                .color(OckamColor::PrimaryResource.color())
         )?;
         Ok(output)
     }
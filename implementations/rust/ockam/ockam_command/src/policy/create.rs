@@ -1,4 +1,5 @@
 use clap::Args;
+use colorful::Colorful;
 
 use ockam::Context;
 use ockam_abac::{Action, Expr, Resource};
@@ -9,7 +10,7 @@ use ockam_core::api::Request;
 use crate::node::get_node_name;
 use crate::policy::policy_path;
 use crate::util::{node_rpc, parse_node_name};
-use crate::CommandGlobalOpts;
+use crate::{fmt_ok, CommandGlobalOpts};
 
 #[derive(Clone, Debug, Args)]
 pub struct CreateCommand {
@@ -43,9 +44,26 @@ async fn run_impl(
 ) -> miette::Result<()> {
     let at = get_node_name(&opts.state, &cmd.at);
     let node_name = parse_node_name(&at)?;
-    let bdy = Policy::new(cmd.expression);
-    let req = Request::post(policy_path(&cmd.resource, &cmd.action)).body(bdy);
+    let policy_path = policy_path(&cmd.resource, &cmd.action);
+    let bdy = Policy::new(cmd.expression.clone());
+    let req = Request::post(&policy_path).body(bdy);
     let node = BackgroundNode::create(ctx, &opts.state, &node_name).await?;
     node.tell(ctx, req).await?;
+
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "Policy with path '{}' has been set to {}",
+            &policy_path,
+            &cmd.expression
+        ))
+        .machine(&policy_path)
+        .json(serde_json::json!({ "policy": {
+            "resource": &cmd.resource.to_string(),
+            "action": &cmd.action.to_string(),
+            "expression": &cmd.expression.to_string(),
+            "at": &node_name}
+        }))
+        .write_line()?;
     Ok(())
 }
@@ -19,7 +19,7 @@ pub struct DeleteCommand {
     #[arg(short, long)]
     resource: Resource,
 
-    #[arg(short, long)]
+    #[arg(short, long, default_value = "handle_message")]
     action: Action,
 
     /// Confirm the deletion without prompting
@@ -27,7 +27,7 @@ pub struct PolicyCommand {
 
 #[derive(Clone, Debug, Subcommand)]
 pub enum PolicySubcommand {
-    #[command(display_order = 900)]
+    #[command(display_order = 900, visible_alias = "set")]
     Create(CreateCommand),
     Show(ShowCommand),
     Delete(DeleteCommand),
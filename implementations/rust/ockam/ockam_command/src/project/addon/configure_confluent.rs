@@ -1,6 +1,7 @@
 use clap::builder::NonEmptyStringValueParser;
 use clap::Args;
 use colorful::Colorful;
+use miette::miette;
 
 use ockam::Context;
 use ockam_api::cloud::addon::{Addons, ConfluentConfig};
@@ -38,6 +39,24 @@ pub struct AddonConfigureConfluentSubcommand {
         value_parser(NonEmptyStringValueParser::new())
     )]
     bootstrap_server: String,
+
+    /// API key used to authenticate to the Kafka cluster. Requires --api-secret
+    #[arg(
+        long,
+        id = "api_key",
+        value_name = "API_KEY",
+        value_parser(NonEmptyStringValueParser::new())
+    )]
+    api_key: Option<String>,
+
+    /// API secret used to authenticate to the Kafka cluster. Requires --api-key
+    #[arg(
+        long,
+        id = "api_secret",
+        value_name = "API_SECRET",
+        value_parser(NonEmptyStringValueParser::new())
+    )]
+    api_secret: Option<String>,
 }
 
 impl AddonConfigureConfluentSubcommand {
@@ -53,9 +72,11 @@ async fn run_impl(
     let AddonConfigureConfluentSubcommand {
         project_name,
         bootstrap_server,
+        api_key,
+        api_secret,
     } = cmd;
+    let config = confluent_config(bootstrap_server, api_key, api_secret)?;
     let project_id = get_project_id(&opts.state, project_name.as_str())?;
-    let config = ConfluentConfig::new(bootstrap_server);
 
     let node = InMemoryNode::start(&ctx, &opts.state).await?;
     let controller = node.create_controller().await?;
@@ -70,3 +91,43 @@ async fn run_impl(
 
     Ok(())
 }
+
+/// Build the addon configuration, checking that the credentials are either both set or both unset
+fn confluent_config(
+    bootstrap_server: String,
+    api_key: Option<String>,
+    api_secret: Option<String>,
+) -> miette::Result<ConfluentConfig> {
+    let config = ConfluentConfig::new(bootstrap_server);
+    match (api_key, api_secret) {
+        (Some(api_key), Some(api_secret)) => Ok(config.with_credentials(api_key, api_secret)),
+        (None, None) => Ok(config),
+        _ => Err(miette!(
+            "The --api-key and --api-secret arguments must be provided together"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confluent_config_credentials() {
+        let config = confluent_config("localhost:9092".into(), None, None).unwrap();
+        assert_eq!(config.api_key, None);
+        assert_eq!(config.api_secret, None);
+
+        let config = confluent_config(
+            "localhost:9092".into(),
+            Some("key".into()),
+            Some("secret".into()),
+        )
+        .unwrap();
+        assert_eq!(config.api_key, Some("key".to_string()));
+        assert_eq!(config.api_secret, Some("secret".to_string()));
+
+        assert!(confluent_config("localhost:9092".into(), Some("key".into()), None).is_err());
+        assert!(confluent_config("localhost:9092".into(), None, Some("secret".into())).is_err());
+    }
+}
@@ -7,9 +7,8 @@ mod list;
 use core::fmt::Write;
 
 use clap::{Args, Subcommand};
-use miette::Context as _;
 
-use ockam_api::cli_state::{CliState, StateDirTrait, StateItemTrait};
+use ockam_api::cli_state::{CliState, CliStateError, StateDirTrait, StateItemTrait};
 use ockam_api::cloud::addon::Addon;
 use ockam_api::cloud::project::Projects;
 use ockam_api::nodes::InMemoryNode;
@@ -103,16 +102,13 @@ impl Output for Vec<Addon> {
     }
 }
 
-pub fn get_project_id(cli_state: &CliState, project_name: &str) -> Result<String> {
-    Ok(cli_state
-        .projects
-        .get(project_name)
-        .context(format!(
-            "Failed to get project {project_name} from config lookup"
-        ))?
-        .config()
-        .id
-        .clone())
+/// Return the id of a locally stored project, or a `ResourceNotFound` error
+/// if the project does not exist. Commands call this before contacting the controller.
+pub fn get_project_id(
+    cli_state: &CliState,
+    project_name: &str,
+) -> std::result::Result<String, CliStateError> {
+    Ok(cli_state.projects.get(project_name)?.config().id.clone())
 }
 
 async fn check_configuration_completion(
@@ -128,3 +124,21 @@ async fn check_configuration_completion(
     let _ = check_project_readiness(opts, ctx, node, project).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_project_id_unknown_project() {
+        let cli_state = CliState::test().unwrap();
+        let result = get_project_id(&cli_state, "unknown");
+        match result {
+            Err(CliStateError::ResourceNotFound { resource, name }) => {
+                assert_eq!(resource, "project");
+                assert_eq!(name, "unknown");
+            }
+            other => panic!("expected a ResourceNotFound error, got {other:?}"),
+        }
+    }
+}
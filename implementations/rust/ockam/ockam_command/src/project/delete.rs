@@ -82,7 +82,7 @@ async fn run_impl(
         // Send request
         controller.delete_project(ctx, space_id, project_id).await?;
 
-        opts.state.projects.delete(&cmd.project_name)?;
+        opts.state.delete_project(&cmd.project_name)?;
         opts.terminal
             .stdout()
             .plain(fmt_ok!(
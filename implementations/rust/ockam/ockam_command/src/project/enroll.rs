@@ -109,7 +109,7 @@ pub async fn project_enroll(
         .create_authority_client(
             project_authority.identity_id(),
             project_authority.address(),
-            Some(identity_name),
+            Some(identity_name.clone()),
         )
         .await?;
 
@@ -130,6 +130,10 @@ pub async fn project_enroll(
     };
 
     let credential = authority_node.issue_credential(ctx).await?;
+    opts.state
+        .identities
+        .get(&identity_name)?
+        .set_enrollment_status_from_credential(&credential)?;
     opts.terminal
         .clone()
         .stdout()
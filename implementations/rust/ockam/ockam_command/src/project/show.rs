@@ -1,11 +1,15 @@
+use std::fmt::Write;
+
 use clap::Args;
+use serde::Serialize;
 
 use ockam::Context;
 use ockam_api::cli_state::{StateDirTrait, StateItemTrait};
-use ockam_api::cloud::project::Projects;
+use ockam_api::cloud::project::{Project, Projects};
 
 use ockam_api::nodes::InMemoryNode;
 
+use crate::output::Output;
 use crate::project::util::refresh_projects;
 use crate::util::api::CloudOpts;
 use crate::util::node_rpc;
@@ -57,9 +61,81 @@ async fn run_impl(ctx: &Context, opts: CommandGlobalOpts, cmd: ShowCommand) -> m
     // Send request
     let project = controller.get_project(ctx, id).await?;
 
-    opts.println(&project)?;
+    opts.println(&ShowProject::from(project.clone()))?;
     opts.state
         .projects
         .overwrite(&project.name, project.clone())?;
     Ok(())
 }
+
+/// Project details, with a flag indicating if the project is ready to be used:
+/// its route and its authority are known
+#[derive(Serialize)]
+struct ShowProject {
+    #[serde(flatten)]
+    project: Project,
+    ready: bool,
+}
+
+impl From<Project> for ShowProject {
+    fn from(project: Project) -> Self {
+        let ready = project.is_ready();
+        Self { project, ready }
+    }
+}
+
+impl Output for ShowProject {
+    fn output(&self) -> crate::error::Result<String> {
+        let mut w = self.project.output()?;
+        write!(
+            w,
+            "\n  Authority route: {}",
+            self.project
+                .authority_access_route
+                .as_deref()
+                .unwrap_or("N/A")
+        )?;
+        write!(
+            w,
+            "\n  Authority identity: {}",
+            self.project.authority_identity.as_deref().unwrap_or("N/A")
+        )?;
+        write!(w, "\n  Ready: {}", self.ready)?;
+        Ok(w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_show_project_readiness() {
+        let mut project = Project {
+            id: "project_id".to_string(),
+            name: "default".to_string(),
+            access_route: "/dnsaddr/localhost/tcp/4000/service/api".to_string(),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(ShowProject::from(project.clone())).unwrap();
+        assert_eq!(json["ready"], false);
+        assert_eq!(json["name"], "default");
+
+        project.identity = Some(
+            "I0a2aa4ac2f36d6c7ac0df4f4ea2e7ecf02b9a4b1"
+                .try_into()
+                .unwrap(),
+        );
+        project.authority_access_route = Some("/dnsaddr/localhost/tcp/4001".to_string());
+        project.authority_identity = Some("0102".to_string());
+        let show = ShowProject::from(project);
+        let json = serde_json::to_value(&show).unwrap();
+        assert_eq!(json["ready"], true);
+        assert_eq!(
+            json["authority_access_route"],
+            "/dnsaddr/localhost/tcp/4001"
+        );
+        assert_eq!(json["authority_identity"], "0102");
+        assert!(show.output().unwrap().contains("Ready: true"));
+    }
+}
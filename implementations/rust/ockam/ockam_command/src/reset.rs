@@ -4,7 +4,7 @@ use crate::{fmt_ok, CommandGlobalOpts};
 use clap::Args;
 use colorful::Colorful;
 use miette::miette;
-use ockam_api::cli_state::CliState;
+use ockam_api::cli_state::{CliState, ResetOptions};
 
 /// Removes the local Ockam configuration including all Identities and Nodes
 #[derive(Clone, Debug, Args)]
@@ -12,6 +12,10 @@ pub struct ResetCommand {
     /// Confirm the reset without prompting
     #[arg(display_order = 901, long, short)]
     yes: bool,
+
+    /// List the files and directories which would be deleted, without deleting them
+    #[arg(long)]
+    dry_run: bool,
 }
 
 impl ResetCommand {
@@ -21,6 +25,16 @@ impl ResetCommand {
 }
 
 fn run_impl(opts: CommandGlobalOpts, cmd: ResetCommand) -> miette::Result<()> {
+    if cmd.dry_run {
+        let paths = opts.state.reset_dry_run(ResetOptions::default());
+        let list = paths
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        opts.terminal.stdout().plain(list).write_line()?;
+        return Ok(());
+    }
     if !cmd.yes {
         match opts
             .terminal
@@ -1,26 +1,32 @@
+use std::future::Future;
 use std::io::Write;
+use std::sync::Arc;
 use std::time::Duration;
 
 use clap::Args;
 use miette::miette;
 use minicbor::{Decode, Decoder, Encode};
+use tokio::sync::Semaphore;
 use tracing::warn;
 
 use ockam::identity::{Identifier, SecureChannelOptions, TrustIdentifierPolicy};
 use ockam::{Context, Node, TcpConnectionOptions, TcpTransport};
 use ockam_api::cli_state::identities::IdentityState;
 use ockam_api::cli_state::traits::{StateDirTrait, StateItemTrait};
-use ockam_api::cli_state::NodeState;
-use ockam_api::nodes::models::base::NodeStatus as NodeStatusModel;
+use ockam_api::cli_state::{CliStateError, NodeState};
 use ockam_api::nodes::{BackgroundNode, NodeManager};
 use ockam_core::api::{Request, ResponseHeader, Status};
-use ockam_core::route;
+use ockam_core::{route, AsyncTryClone};
 use ockam_node::MessageSendReceiveOptions;
 
-use crate::util::{api, node_rpc};
+use crate::node::util::{get_node_liveness, DEFAULT_NODE_STATUS_TIMEOUT};
+use crate::util::node_rpc;
 use crate::CommandGlobalOpts;
 use crate::Result;
 
+/// Maximum number of nodes queried at the same time
+const MAX_CONCURRENT_NODE_QUERIES: usize = 8;
+
 /// Display information about the system's status
 #[derive(Clone, Debug, Args)]
 pub struct StatusCommand {
@@ -37,6 +43,12 @@ impl StatusCommand {
     pub fn run(self, options: CommandGlobalOpts) {
         node_rpc(rpc, (options, self));
     }
+
+    /// Timeout used when querying the status of each node.
+    /// It can't be lower than the default node status timeout.
+    fn node_status_timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout).max(DEFAULT_NODE_STATUS_TIMEOUT)
+    }
 }
 
 async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, StatusCommand)) -> miette::Result<()> {
@@ -49,43 +61,93 @@ async fn run_impl(
     cmd: StatusCommand,
 ) -> miette::Result<()> {
     let identities_details = get_identities_details(&opts, cmd.all)?;
-    let nodes_details = get_nodes_details(ctx, &opts).await?;
+    let nodes_details = get_nodes_details(ctx, &opts, cmd.node_status_timeout()).await?;
     let orchestrator_version =
         get_orchestrator_version(ctx, &opts, Duration::from_secs(cmd.timeout)).await;
-    let status = StatusData::from_parts(orchestrator_version, identities_details, nodes_details)?;
+    let local_state = opts.state.health_check();
+    let status = StatusData::from_parts(
+        orchestrator_version,
+        local_state,
+        identities_details,
+        nodes_details,
+    )?;
     print_output(opts, cmd, status)?;
     Ok(())
 }
 
-async fn get_nodes_details(ctx: &Context, opts: &CommandGlobalOpts) -> Result<Vec<NodeDetails>> {
-    let mut node_details: Vec<NodeDetails> = vec![];
-
-    let node_states = opts.state.nodes.list()?;
+async fn get_nodes_details(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    timeout: Duration,
+) -> Result<Vec<NodeDetails>> {
+    let mut node_states = opts.state.nodes.list()?;
     if node_states.is_empty() {
-        return Ok(node_details);
+        return Ok(vec![]);
     }
-    let mut node = BackgroundNode::create(ctx, &opts.state, "default").await?;
-    node.set_timeout(Duration::from_millis(200));
+    node_states.sort_by(|a, b| a.name().cmp(b.name()));
+
+    let node = BackgroundNode::create(ctx, &opts.state, "default").await?;
 
+    // each query runs in its own task, with its own context
+    let mut queries = Vec::with_capacity(node_states.len());
     for node_state in &node_states {
+        let mut node = node.clone();
         node.set_node_name(node_state.name());
-        let node_infos = NodeDetails {
-            identifier: node_state.config().identifier()?,
-            state: node_state.clone(),
-            status: get_node_status(ctx, &node).await?,
-        };
-        node_details.push(node_infos);
+        queries.push((ctx.async_try_clone().await?, node));
     }
+    let liveness = query_concurrently(queries, |(ctx, node)| async move {
+        get_node_liveness(&ctx, &node, timeout).await
+    })
+    .await;
 
-    Ok(node_details)
+    let mut nodes_details = Vec::with_capacity(node_states.len());
+    for (node_state, liveness) in node_states.into_iter().zip(liveness) {
+        let status = match liveness {
+            Some(liveness) => liveness.to_string(),
+            None => "Unknown (the node could not be queried)".to_string(),
+        };
+        nodes_details.push(NodeDetails {
+            identifier: node_state.config().identifier()?,
+            status,
+            state: node_state,
+        });
+    }
+    Ok(nodes_details)
 }
 
-async fn get_node_status(ctx: &Context, node: &BackgroundNode) -> Result<String> {
-    let node_status_model: miette::Result<NodeStatusModel> =
-        node.ask(ctx, api::query_status()).await;
-    Ok(node_status_model
-        .map(|m| m.status)
-        .unwrap_or("Stopped".to_string()))
+/// Run a query for each item, with at most `MAX_CONCURRENT_NODE_QUERIES` queries in flight.
+/// The results are returned in the same order as the items.
+/// A query which panics or is cancelled returns `None`, without affecting the other queries.
+async fn query_concurrently<T, F, Fut>(items: Vec<T>, mut query: F) -> Vec<Option<Fut::Output>>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_NODE_QUERIES));
+    let tasks: Vec<_> = items
+        .into_iter()
+        .map(|item| {
+            let permits = permits.clone();
+            let query = query(item);
+            tokio::spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                query.await
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        match task.await {
+            Ok(result) => results.push(Some(result)),
+            Err(e) => {
+                warn!(%e, "A node query failed");
+                results.push(None)
+            }
+        }
+    }
+    results
 }
 
 fn get_identities_details(opts: &CommandGlobalOpts, all: bool) -> Result<Vec<IdentityState>> {
@@ -201,6 +263,7 @@ fn build_plain_output(
         "Project version: {}",
         status.orchestrator_version.project_version
     )?;
+    writeln!(&mut plain, "Local state: {}", status.local_state)?;
     if status.identities.is_empty() {
         if cmd.all {
             writeln!(&mut plain, "No identities found!")?;
@@ -239,14 +302,16 @@ fn build_plain_output(
 struct StatusData {
     #[serde(flatten)]
     orchestrator_version: OrchestratorVersionInfo,
+    local_state: String,
     identities: Vec<IdentityWithLinkedNodes>,
 }
 
 impl StatusData {
     fn from_parts(
         orchestrator_version: Result<OrchestratorVersionInfo>,
+        local_state: std::result::Result<(), CliStateError>,
         identities_details: Vec<IdentityState>,
-        mut nodes_details: Vec<NodeDetails>,
+        nodes_details: Vec<NodeDetails>,
     ) -> Result<Self> {
         let orchestrator_version = orchestrator_version
             .map_err(|e| warn!(%e, "Failed to retrieve orchestrator version"))
@@ -254,31 +319,39 @@ impl StatusData {
                 controller_version: "N/A".to_string(),
                 project_version: "N/A".to_string(),
             });
+        let local_state = local_state_status(local_state);
         let mut identities = vec![];
         for identity in identities_details.into_iter() {
-            let mut identity_status = IdentityWithLinkedNodes {
-                identity,
-                nodes: vec![],
-            };
-            nodes_details
-                .retain(|nd| nd.identifier == identity_status.identity.config().identifier());
-            if !nodes_details.is_empty() {
-                for node in nodes_details.iter() {
-                    identity_status.nodes.push(NodeStatus {
-                        name: node.state.name().to_string(),
-                        status: node.status.clone(),
-                    });
-                }
-            }
-            identities.push(identity_status);
+            let identifier = identity.config().identifier();
+            let nodes = nodes_details
+                .iter()
+                .filter(|nd| nd.identifier == identifier)
+                .map(|nd| NodeStatus {
+                    name: nd.state.name().to_string(),
+                    status: nd.status.clone(),
+                })
+                .collect();
+            identities.push(IdentityWithLinkedNodes { identity, nodes });
         }
         Ok(Self {
             orchestrator_version,
+            local_state,
             identities,
         })
     }
 }
 
+/// Summarize the result of the local state health check
+fn local_state_status(health: std::result::Result<(), CliStateError>) -> String {
+    match health {
+        Ok(()) => "OK".to_string(),
+        Err(e) => {
+            warn!(%e, "The local state is degraded");
+            format!("Degraded ({e})")
+        }
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 struct IdentityWithLinkedNodes {
     identity: IdentityState,
@@ -309,3 +382,66 @@ struct OrchestratorVersionInfo {
     #[n(2)]
     project_version: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_status_timeout() {
+        let cmd = StatusCommand {
+            all: false,
+            timeout: 5,
+        };
+        assert_eq!(cmd.node_status_timeout(), Duration::from_secs(5));
+
+        let cmd = StatusCommand {
+            all: false,
+            timeout: 0,
+        };
+        assert_eq!(cmd.node_status_timeout(), DEFAULT_NODE_STATUS_TIMEOUT);
+    }
+
+    #[test]
+    fn test_local_state_status() {
+        assert_eq!(local_state_status(Ok(())), "OK");
+        assert_eq!(
+            local_state_status(Err(CliStateError::InvalidOperation(
+                "The node 'n1' can't be loaded".to_string()
+            ))),
+            "Degraded (The node 'n1' can't be loaded)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_concurrently() {
+        let latencies = vec![300, 100, 200, 50, 250];
+        let start = std::time::Instant::now();
+        let results = query_concurrently(latencies.clone(), |latency| async move {
+            tokio::time::sleep(Duration::from_millis(latency)).await;
+            latency
+        })
+        .await;
+        let elapsed = start.elapsed();
+
+        // the results are returned in order
+        assert_eq!(results, latencies.into_iter().map(Some).collect::<Vec<_>>());
+        // the total time is close to the slowest query, not to the sum of all the queries
+        assert!(elapsed >= Duration::from_millis(300));
+        assert!(elapsed < Duration::from_millis(600));
+    }
+
+    #[tokio::test]
+    async fn test_query_concurrently_survives_a_panicking_query() {
+        let results = query_concurrently(vec![1, 2, 3], |n| async move {
+            if n == 2 {
+                panic!("the query for {n} failed");
+            }
+            n
+        })
+        .await;
+
+        // the other queries are still reported
+        assert_eq!(results, vec![Some(1), None, Some(3)]);
+    }
+}
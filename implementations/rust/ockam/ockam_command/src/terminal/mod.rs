@@ -383,6 +383,15 @@ impl<W: TerminalWriter> Terminal<W, ToStdOut> {
     }
 
     pub fn write_line(self) -> Result<()> {
+        let msg = self.output_message()?;
+        self.stdout.write_line(msg)
+    }
+
+    /// Select the message to write, depending on the output format and on the terminal.
+    ///
+    /// In quiet mode, or when stdout is not a TTY, the machine message is preferred
+    /// so that scripts can capture the command's result.
+    fn output_message(&self) -> Result<&str> {
         // Check that there is at least one output format defined
         if self.mode.output.plain.is_none()
             && self.mode.output.machine.is_none()
@@ -397,7 +406,7 @@ impl<W: TerminalWriter> Terminal<W, ToStdOut> {
 
         let msg = match self.output_format {
             OutputFormat::Plain => {
-                if self.stdout.is_tty() {
+                if self.stdout.is_tty() && !self.quiet {
                     // If not set, fallback with the following priority: Machine -> JSON
                     match (plain, machine, json) {
                         (Some(plain), _, _) => plain,
@@ -420,7 +429,7 @@ impl<W: TerminalWriter> Terminal<W, ToStdOut> {
                 json.ok_or(miette!("JSON output is not defined for this command"))?
             }
         };
-        self.stdout.write_line(msg)
+        Ok(msg)
     }
 }
 
@@ -489,3 +498,85 @@ impl<W: TerminalWriter> Terminal<W> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// A writer simulating a TTY and recording the lines written to it
+    #[derive(Clone)]
+    struct TtyWriter {
+        lines: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl TerminalWriter for TtyWriter {
+        fn stdout(_no_color: bool) -> Self {
+            Self {
+                lines: Arc::new(Mutex::new(vec![])),
+            }
+        }
+
+        fn stderr(no_color: bool) -> Self {
+            Self::stdout(no_color)
+        }
+
+        fn is_tty(&self) -> bool {
+            true
+        }
+
+        fn write(&mut self, s: impl AsRef<str>) -> Result<()> {
+            self.write_line(s)
+        }
+
+        fn rewrite(&mut self, s: impl AsRef<str>) -> Result<()> {
+            self.write_line(s)
+        }
+
+        fn write_line(&self, s: impl AsRef<str>) -> Result<()> {
+            self.lines.lock().unwrap().push(s.as_ref().to_string());
+            Ok(())
+        }
+    }
+
+    fn written_lines(quiet: bool) -> Vec<String> {
+        // stdout and stderr share the same recorded lines
+        let writer = TtyWriter::stdout(false);
+        let lines = writer.lines.clone();
+        let terminal = Terminal {
+            stdout: writer.clone(),
+            stderr: writer,
+            quiet,
+            no_input: true,
+            output_format: OutputFormat::Plain,
+            mode: ToStdErr,
+        };
+        terminal.write_line("a log message").unwrap();
+        terminal
+            .stdout()
+            .plain("Identity I123 created successfully")
+            .machine("I123")
+            .json(r#"{"identity":{"identifier":"I123"}}"#)
+            .write_line()
+            .unwrap();
+        let lines = lines.lock().unwrap().clone();
+        lines
+    }
+
+    #[test]
+    fn test_plain_output_on_tty() {
+        assert_eq!(
+            written_lines(false),
+            vec![
+                "a log message".to_string(),
+                "Identity I123 created successfully".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quiet_mode_only_prints_the_machine_value() {
+        assert_eq!(written_lines(true), vec!["I123".to_string()]);
+    }
+}
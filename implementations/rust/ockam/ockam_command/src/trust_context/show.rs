@@ -32,7 +32,7 @@ fn run_impl(opts: CommandGlobalOpts, cmd: ShowCommand) -> miette::Result<()> {
         .unwrap_or(opts.state.trust_contexts.default()?.name().to_string());
     let state = opts.state.trust_contexts.get(name)?;
     let plain_output = {
-        let mut output = "Trust context:".to_string();
+        let mut output = "Trust context:\n".to_string();
         for line in state.to_string().lines() {
             output.push_str(&format!("{:2}{}\n", "", line));
         }
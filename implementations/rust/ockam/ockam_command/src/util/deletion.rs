@@ -0,0 +1,117 @@
+use std::fmt::Display;
+
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use serde::Serialize;
+
+use crate::{fmt_err, fmt_ok, CommandGlobalOpts};
+
+/// Outcome of the deletion of several resources (nodes, identities, ...).
+/// A failure to delete one resource doesn't stop the deletion of the other resources
+#[derive(Debug, Serialize)]
+pub struct DeletionReport<T> {
+    /// Names of the deleted resources
+    pub deleted: Vec<T>,
+    /// Resources which could not be deleted, with the reason of the failure
+    pub failed: Vec<DeletionFailure<T>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeletionFailure<T> {
+    pub name: T,
+    pub error: String,
+}
+
+impl<T> Default for DeletionReport<T> {
+    fn default() -> Self {
+        Self {
+            deleted: vec![],
+            failed: vec![],
+        }
+    }
+}
+
+impl<T> DeletionReport<T> {
+    /// Record the result of the deletion of one resource
+    pub fn record<E: Display>(&mut self, name: T, result: Result<(), E>) {
+        match result {
+            Ok(()) => self.deleted.push(name),
+            Err(e) => self.failed.push(DeletionFailure {
+                name,
+                error: e.to_string(),
+            }),
+        }
+    }
+}
+
+impl<T: Display + Serialize> DeletionReport<T> {
+    /// Write the report to the terminal, as plain text or JSON.
+    /// `resource` and `resources` are the singular and plural names of the deleted resources,
+    /// e.g. "node" and "nodes".
+    ///
+    /// An error is returned if some resources could not be deleted
+    pub fn write(
+        &self,
+        opts: &CommandGlobalOpts,
+        resource: &str,
+        resources: &str,
+    ) -> miette::Result<()> {
+        let mut plain = String::new();
+        for name in &self.deleted {
+            plain.push_str(&fmt_ok!(
+                "The {} named '{}' has been deleted\n",
+                resource,
+                name
+            ));
+        }
+        for failure in &self.failed {
+            plain.push_str(&fmt_err!(
+                "The {} named '{}' could not be deleted: {}\n",
+                resource,
+                failure.name,
+                failure.error
+            ));
+        }
+        if self.failed.is_empty() {
+            plain.push_str(&fmt_ok!("All {} have been deleted", resources));
+        }
+        opts.terminal
+            .stdout()
+            .plain(plain.trim_end())
+            .json(serde_json::to_value(self).into_diagnostic()?)
+            .write_line()?;
+        if !self.failed.is_empty() {
+            return Err(miette!(
+                "{} of the {} could not be deleted",
+                self.failed.len(),
+                resources
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_deletions() {
+        let mut report = DeletionReport::default();
+        report.record("n1", Ok::<(), String>(()));
+        report.record("n2", Err("the node is still running"));
+        assert_eq!(report.deleted, vec!["n1"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].name, "n2");
+        assert_eq!(report.failed[0].error, "the node is still running");
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "deleted": ["n1"],
+                "failed": [{ "name": "n2", "error": "the node is still running" }]
+            })
+        );
+    }
+}
@@ -21,6 +21,7 @@ use ockam_multiaddr::{
 use crate::Result;
 
 pub mod api;
+pub mod deletion;
 pub mod duration;
 pub mod exitcode;
 pub mod parsers;
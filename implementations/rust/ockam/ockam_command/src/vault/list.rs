@@ -4,6 +4,7 @@ use miette::IntoDiagnostic;
 use ockam_api::cli_state::traits::StateDirTrait;
 
 use crate::util::local_cmd;
+use crate::vault::VaultOutput;
 use crate::{docs, CommandGlobalOpts};
 
 const LONG_ABOUT: &str = include_str!("./static/list/long_about.txt");
@@ -26,7 +27,13 @@ impl ListCommand {
 }
 
 fn run_impl(opts: CommandGlobalOpts) -> miette::Result<()> {
-    let vaults = opts.state.vaults.list()?;
+    let vaults = opts
+        .state
+        .vaults
+        .list()?
+        .into_iter()
+        .map(|vault| VaultOutput::new(&opts.state, vault))
+        .collect::<crate::Result<Vec<_>>>()?;
     let plain = opts
         .terminal
         .build_list(&vaults, "Vaults", "No vaults found on this system.")?;
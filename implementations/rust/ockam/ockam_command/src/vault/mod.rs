@@ -11,9 +11,14 @@ use crate::vault::list::ListCommand;
 use crate::vault::show::ShowCommand;
 use crate::{docs, CommandGlobalOpts};
 
+use std::fmt::Write;
+
 use clap::{Args, Subcommand};
 use ockam_api::cli_state::traits::StateDirTrait;
-use ockam_api::cli_state::CliState;
+use ockam_api::cli_state::{CliState, VaultState};
+use serde::Serialize;
+
+use crate::output::Output;
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
 
@@ -56,3 +61,73 @@ pub fn default_vault_name(cli_state: &CliState) -> String {
         .default()
         .map_or("default".to_string(), |v| v.name().to_string())
 }
+
+/// A vault, as displayed by the `vault list` and `vault show` commands
+#[derive(Serialize)]
+pub struct VaultOutput {
+    #[serde(flatten)]
+    vault: VaultState,
+    is_default: bool,
+}
+
+impl VaultOutput {
+    pub fn new(cli_state: &CliState, vault: VaultState) -> crate::Result<Self> {
+        let is_default = cli_state.vaults.is_default(vault.name())?;
+        Ok(Self { vault, is_default })
+    }
+}
+
+impl Output for VaultOutput {
+    fn output(&self) -> crate::Result<String> {
+        let mut output = self.vault.output()?;
+        writeln!(output, "Default: {}", self.is_default)?;
+        Ok(output)
+    }
+
+    fn list_output(&self) -> crate::Result<String> {
+        let mut output = self.vault.list_output()?;
+        if self.is_default {
+            // the vault name is displayed on the first line
+            let end_of_name = output.find('\n').unwrap_or(output.len());
+            output.insert_str(end_of_name, " (default)");
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_api::cli_state::VaultConfig;
+
+    #[test]
+    fn test_vault_output() {
+        let cli_state = CliState::test().unwrap();
+        let software = cli_state
+            .vaults
+            .create("software", VaultConfig::default())
+            .unwrap();
+        let kms = cli_state
+            .vaults
+            .create("kms", VaultConfig::new(true).unwrap())
+            .unwrap();
+
+        let software = VaultOutput::new(&cli_state, software).unwrap();
+        let output = software.output().unwrap();
+        assert!(output.contains("Name: software"));
+        assert!(output.contains("Type: OCKAM"));
+        assert!(output.contains("Default: true"));
+        assert!(software.list_output().unwrap().contains("(default)"));
+
+        let kms = VaultOutput::new(&cli_state, kms).unwrap();
+        let output = kms.output().unwrap();
+        assert!(output.contains("Name: kms"));
+        assert!(output.contains("Type: AWS KMS"));
+        assert!(output.contains("Default: false"));
+        assert!(!kms.list_output().unwrap().contains("(default)"));
+
+        let json = serde_json::to_value(&kms).unwrap();
+        assert_eq!(json["name"], "kms");
+        assert_eq!(json["is_default"], false);
+    }
+}
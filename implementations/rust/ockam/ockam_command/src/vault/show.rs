@@ -5,7 +5,9 @@ use miette::IntoDiagnostic;
 
 use ockam_api::cli_state::traits::StateDirTrait;
 
+use crate::output::Output;
 use crate::util::local_cmd;
+use crate::vault::VaultOutput;
 use crate::{docs, CommandGlobalOpts};
 
 const LONG_ABOUT: &str = include_str!("./static/show/long_about.txt");
@@ -34,7 +36,7 @@ fn run_impl(opts: CommandGlobalOpts, cmd: ShowCommand) -> miette::Result<()> {
     let name = cmd
         .name
         .unwrap_or(opts.state.vaults.default()?.name().to_string());
-    let state = opts.state.vaults.get(name)?;
+    let state = VaultOutput::new(&opts.state, opts.state.vaults.get(name)?)?;
 
     let json = serde_json::to_string_pretty(&state).into_diagnostic()?;
 
@@ -42,7 +44,7 @@ fn run_impl(opts: CommandGlobalOpts, cmd: ShowCommand) -> miette::Result<()> {
         let mut buf = String::new();
 
         writeln!(buf, "Vault:").into_diagnostic()?;
-        for line in state.to_string().lines() {
+        for line in state.output()?.lines() {
             writeln!(buf, "{:2}{}", "", line).into_diagnostic()?;
         }
         buf
@@ -0,0 +1,33 @@
+#!/bin/bash
+
+# ===== SETUP
+
+setup() {
+  load load/base.bash
+  load_bats_ext
+  setup_home_dir
+}
+
+teardown() {
+  teardown_home_dir
+}
+
+# ===== TESTS
+
+@test "policy - set, list and delete" {
+  run_success "$OCKAM" node create n1
+
+  run_success "$OCKAM" policy set --at n1 --resource tcp-outlet --action handle_message --expression '(= subject.component "edge")'
+  assert_output --partial "/policy/tcp-outlet/handle_message"
+
+  run_success "$OCKAM" policy list --at n1 --resource tcp-outlet
+  assert_output --partial "handle_message"
+  assert_output --partial '(= subject.component "edge")'
+
+  run_success "$OCKAM" policy delete --at n1 --resource tcp-outlet --action handle_message --yes
+  run_success "$OCKAM" policy list --at n1 --resource tcp-outlet
+  assert_output --partial "No Policies on Node n1 for tcp-outlet"
+
+  # Invalid expressions are rejected
+  run_failure "$OCKAM" policy set --at n1 --resource tcp-outlet --expression '(unknown subject.component)'
+}
@@ -86,4 +86,9 @@ impl AuthorityService {
     pub fn identifier(&self) -> &Identifier {
         &self.identifier
     }
+
+    /// Return true if this authority can provide a credential for the current identity
+    pub fn has_own_credential(&self) -> bool {
+        self.own_credential.is_some()
+    }
 }
@@ -35,6 +35,15 @@ impl TrustContext {
             .ok_or_else(|| IdentityError::UnknownAuthority.into())
     }
 
+    /// Return true if credentials must be presented to the other party,
+    /// which is the case when the authority of this trust context provides our own credential
+    pub fn requires_credentials(&self) -> bool {
+        self.authority
+            .as_ref()
+            .map(|a| a.has_own_credential())
+            .unwrap_or(false)
+    }
+
     /// Return the authority identities attached to this trust context
     pub async fn authorities(&self) -> Result<Vec<Identifier>> {
         Ok(vec![self.authority()?.identifier().clone()])
@@ -1,6 +1,7 @@
 use crate::models::{Identifier, TimestampInSeconds};
+use core::str::from_utf8;
 use minicbor::{Decode, Encode};
-use ockam_core::compat::borrow::ToOwned;
+use ockam_core::compat::borrow::{Cow, ToOwned};
 use ockam_core::compat::{collections::BTreeMap, vec::Vec};
 use serde::{Deserialize, Serialize};
 
@@ -41,6 +42,24 @@ impl AttributesEntry {
         &self.attrs
     }
 
+    /// Return the value of an attribute if it is valid UTF-8
+    pub fn get_str(&self, name: &str) -> Option<Cow<'_, str>> {
+        let value = self.attrs.get(name.as_bytes())?;
+        from_utf8(value).ok().map(Cow::Borrowed)
+    }
+
+    /// Return the value of an attribute if it is the text representation of an integer
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        self.get_str(name)?.trim().parse().ok()
+    }
+
+    /// Iterate over the attributes which names and values are valid UTF-8
+    pub fn utf8_attrs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attrs
+            .iter()
+            .filter_map(|(k, v)| Some((from_utf8(k).ok()?, from_utf8(v).ok()?)))
+    }
+
     /// Expiration time for this entry
     pub fn expires(&self) -> Option<TimestampInSeconds> {
         self.expires
@@ -56,3 +75,28 @@ impl AttributesEntry {
         self.attested_by.to_owned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_accessors() {
+        let mut attrs = BTreeMap::new();
+        attrs.insert(b"role".to_vec(), b"member".to_vec());
+        attrs.insert(b"level".to_vec(), b"42".to_vec());
+        attrs.insert(b"binary".to_vec(), vec![0xff, 0xfe]);
+        let entry = AttributesEntry::new(attrs, TimestampInSeconds(0), None, None);
+
+        assert_eq!(entry.get_str("role").as_deref(), Some("member"));
+        assert_eq!(entry.get_str("binary"), None);
+        assert_eq!(entry.get_str("missing"), None);
+
+        assert_eq!(entry.get_i64("level"), Some(42));
+        assert_eq!(entry.get_i64("role"), None);
+        assert_eq!(entry.get_i64("binary"), None);
+
+        let utf8_attrs: Vec<(&str, &str)> = entry.utf8_attrs().collect();
+        assert_eq!(utf8_attrs, vec![("level", "42"), ("role", "member")]);
+    }
+}
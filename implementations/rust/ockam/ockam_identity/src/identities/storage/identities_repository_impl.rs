@@ -89,6 +89,16 @@ impl IdentityAttributesReader for IdentitiesStorage {
         }
         Ok(l)
     }
+
+    /// The entries are counted without being decoded, so entries which have expired
+    /// but were not read since their expiration are still counted
+    async fn count(&self) -> Result<usize> {
+        Ok(self
+            .storage
+            .keys(IdentityConstants::ATTRIBUTES_KEY)
+            .await?
+            .len())
+    }
 }
 
 #[async_trait]
@@ -149,6 +159,15 @@ impl IdentitiesWriter for IdentitiesStorage {
             )
             .await
     }
+
+    async fn delete_identity(&self, identifier: &Identifier) -> Result<()> {
+        self.storage
+            .del(
+                &identifier.to_string(),
+                IdentityConstants::CHANGE_HISTORY_KEY,
+            )
+            .await
+    }
 }
 
 #[async_trait]
@@ -167,4 +186,144 @@ impl IdentitiesReader for IdentitiesStorage {
             Ok(None)
         }
     }
+
+    async fn get_identities_count(&self) -> Result<usize> {
+        Ok(self
+            .storage
+            .keys(IdentityConstants::CHANGE_HISTORY_KEY)
+            .await?
+            .len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_attributes_many() -> Result<()> {
+        let repository = IdentitiesStorage::create();
+        let identifier1 = Identifier::try_from("Ie86be15e83d1c93e24dd1967010b01b6df491b45")?;
+        let identifier2 = Identifier::try_from("I47e4ae35f3b6f6b8e1e4bfc82a4c7b4c2e5d8f61")?;
+        let identifier3 = Identifier::try_from("I0d6d6b0f2f5cfb9f4a86a3b5a6e3b7c1f0e2d4c8")?;
+
+        let entry1 = attributes_entry(b"project", b"1")?;
+        let entry3 = attributes_entry(b"project", b"3")?;
+        repository
+            .put_attributes(&identifier1, entry1.clone())
+            .await?;
+        repository
+            .put_attributes(&identifier3, entry3.clone())
+            .await?;
+
+        let result = repository
+            .get_attributes_many(&[identifier1.clone(), identifier2, identifier3.clone()])
+            .await?;
+        assert_eq!(result, vec![(identifier1, entry1), (identifier3, entry3)]);
+
+        assert!(repository.get_attributes_many(&[]).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_by_attested_by() -> Result<()> {
+        let repository = IdentitiesStorage::create();
+        let authority = Identifier::try_from("Ie86be15e83d1c93e24dd1967010b01b6df491b45")?;
+        let subject1 = Identifier::try_from("I47e4ae35f3b6f6b8e1e4bfc82a4c7b4c2e5d8f61")?;
+        let subject2 = Identifier::try_from("I0d6d6b0f2f5cfb9f4a86a3b5a6e3b7c1f0e2d4c8")?;
+
+        // attested by the authority
+        let entry1 = attested_entry(&authority)?;
+        repository.put_attributes(&subject1, entry1.clone()).await?;
+        // self-attested by the authority
+        let authority_entry = attested_entry(&authority)?;
+        repository
+            .put_attributes(&authority, authority_entry.clone())
+            .await?;
+        // self-attested by another subject
+        repository
+            .put_attributes(&subject2, attested_entry(&subject2)?)
+            .await?;
+
+        let result = repository.list_by_attested_by(&authority, false).await?;
+        assert_eq!(result, vec![(subject1.clone(), entry1.clone())]);
+
+        let mut result = repository.list_by_attested_by(&authority, true).await?;
+        result.sort_by(|a, b| a.0.to_string().cmp(&b.0.to_string()));
+        assert_eq!(
+            result,
+            vec![(subject1, entry1), (authority, authority_entry)]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_identity() -> Result<()> {
+        let repository = IdentitiesStorage::create();
+        let identifier = Identifier::try_from("Ie86be15e83d1c93e24dd1967010b01b6df491b45")?;
+        repository
+            .update_identity(&identifier, &ChangeHistory(vec![]))
+            .await?;
+        assert!(repository.retrieve_identity(&identifier).await?.is_some());
+
+        repository.delete_identity(&identifier).await?;
+        assert!(repository.retrieve_identity(&identifier).await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_counts() -> Result<()> {
+        let repository = IdentitiesStorage::create();
+        assert_eq!(repository.get_identities_count().await?, 0);
+        assert_eq!(repository.count().await?, 0);
+
+        let identifiers = [
+            Identifier::try_from("Ie86be15e83d1c93e24dd1967010b01b6df491b45")?,
+            Identifier::try_from("I47e4ae35f3b6f6b8e1e4bfc82a4c7b4c2e5d8f61")?,
+            Identifier::try_from("I0d6d6b0f2f5cfb9f4a86a3b5a6e3b7c1f0e2d4c8")?,
+        ];
+        for identifier in &identifiers {
+            repository
+                .update_identity(identifier, &ChangeHistory(vec![]))
+                .await?;
+        }
+        for identifier in &identifiers[..2] {
+            repository
+                .put_attributes(identifier, attributes_entry(b"project", b"1")?)
+                .await?;
+        }
+        // updating an existing entry doesn't change the counts
+        repository
+            .update_identity(&identifiers[0], &ChangeHistory(vec![]))
+            .await?;
+        repository
+            .put_attribute_value(&identifiers[0], b"role".to_vec(), b"member".to_vec())
+            .await?;
+
+        assert_eq!(repository.get_identities_count().await?, 3);
+        assert_eq!(repository.count().await?, 2);
+
+        repository.delete_identity(&identifiers[2]).await?;
+        repository.delete(&identifiers[1]).await?;
+        assert_eq!(repository.get_identities_count().await?, 2);
+        assert_eq!(repository.count().await?, 1);
+        Ok(())
+    }
+
+    fn attributes_entry(name: &[u8], value: &[u8]) -> Result<AttributesEntry> {
+        let mut attributes = BTreeMap::new();
+        attributes.insert(name.to_vec(), value.to_vec());
+        Ok(AttributesEntry::new(attributes, now()?, None, None))
+    }
+
+    fn attested_entry(attested_by: &Identifier) -> Result<AttributesEntry> {
+        let mut attributes = BTreeMap::new();
+        attributes.insert(b"role".to_vec(), b"member".to_vec());
+        Ok(AttributesEntry::new(
+            attributes,
+            now()?,
+            None,
+            Some(attested_by.clone()),
+        ))
+    }
 }
@@ -34,6 +34,46 @@ pub trait IdentityAttributesReader: Send + Sync + 'static {
 
     /// List all identities with their attributes
     async fn list(&self) -> Result<Vec<(Identifier, AttributesEntry)>>;
+
+    /// Return the number of identities with attributes.
+    /// Implementations should override this method when they can count entries without loading them
+    async fn count(&self) -> Result<usize> {
+        Ok(self.list().await?.len())
+    }
+
+    /// Get the attributes associated with several identity identifiers at once.
+    /// Identifiers without attributes are not part of the result
+    async fn get_attributes_many(
+        &self,
+        identities: &[Identifier],
+    ) -> Result<Vec<(Identifier, AttributesEntry)>> {
+        let mut result = Vec::new();
+        for identity in identities {
+            if let Some(entry) = self.get_attributes(identity).await? {
+                result.push((identity.clone(), entry))
+            }
+        }
+        Ok(result)
+    }
+
+    /// List the identities with attributes attested by a given authority.
+    /// Self-attested attributes, where the subject is its own attester, are excluded
+    /// unless `include_self_attested` is true
+    async fn list_by_attested_by(
+        &self,
+        authority: &Identifier,
+        include_self_attested: bool,
+    ) -> Result<Vec<(Identifier, AttributesEntry)>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|(subject, entry)| {
+                entry.attested_by().as_ref() == Some(authority)
+                    && (include_self_attested || subject != authority)
+            })
+            .collect())
+    }
 }
 
 /// Trait implementing write access to attributes
@@ -64,6 +104,9 @@ pub trait IdentitiesWriter: Send + Sync + 'static {
         identifier: &Identifier,
         change_history: &ChangeHistory,
     ) -> Result<()>;
+
+    /// Remove the change history of an identity
+    async fn delete_identity(&self, identifier: &Identifier) -> Result<()>;
 }
 
 /// Trait implementing read access to identiets
@@ -72,6 +115,9 @@ pub trait IdentitiesReader: Send + Sync + 'static {
     /// Return a persisted identity
     async fn retrieve_identity(&self, identifier: &Identifier) -> Result<Option<ChangeHistory>>;
 
+    /// Return the number of persisted identities
+    async fn get_identities_count(&self) -> Result<usize>;
+
     /// Return a persisted identity that is expected to be present and return and Error if this is not the case
     async fn get_identity(&self, identifier: &Identifier) -> Result<ChangeHistory> {
         match self.retrieve_identity(identifier).await? {
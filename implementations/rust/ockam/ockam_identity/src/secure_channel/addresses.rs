@@ -20,6 +20,9 @@ pub(crate) struct Addresses {
     pub(crate) encryptor: Address,
     // Used to decrypt messages that were received though some channel other than Ockam Routing from the other end of the channel
     pub(crate) encryptor_api: Address,
+
+    // Used to receive the notification that the handshake took too long
+    pub(crate) handshake_timeout: Address,
 }
 
 impl Addresses {
@@ -36,12 +39,16 @@ impl Addresses {
         let encryptor_api =
             Address::random_tagged(&format!("SecureChannel.{}.encryptor.api", role_str));
 
+        let handshake_timeout =
+            Address::random_tagged(&format!("SecureChannel.{}.handshake.timeout", role_str));
+
         Self {
             decryptor_internal,
             decryptor_remote,
             decryptor_api,
             encryptor,
             encryptor_api,
+            handshake_timeout,
         }
     }
 }
@@ -1,8 +1,10 @@
+use core::time::Duration;
 use minicbor::{Decode, Encode};
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
-use ockam_core::{async_trait, Result};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Error, Result};
 use ockam_vault::{AeadSecretKeyHandle, X25519PublicKey};
 use tracing::{debug, warn};
 
@@ -10,22 +12,28 @@ use crate::models::{
     ChangeHistory, CredentialAndPurposeKey, Identifier, PurposeKeyAttestation, PurposePublicKey,
 };
 use crate::{
-    Identities, Identity, IdentityError, SecureChannelTrustInfo, TrustContext, TrustPolicy,
+    Identities, Identity, IdentityError, Role, SecureChannelTrustInfo, TrustContext, TrustPolicy,
 };
 
+/// Maximum duration of a handshake, when no timeout is specified
+pub(super) const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Interface for a state machine in a key exchange protocol
 #[async_trait]
 pub(super) trait StateMachine: Send + Sync + 'static {
     async fn on_event(&mut self, event: Event) -> Result<Action>;
     fn get_handshake_results(&self) -> Option<HandshakeResults>;
+    /// Maximum duration of the handshake, after which a `Timeout` event must be sent
+    fn handshake_timeout(&self) -> Duration;
 }
 
-/// Events received by the state machine, either initializing the state machine
-/// or receiving a message from the other party
+/// Events received by the state machine, either initializing the state machine,
+/// receiving a message from the other party or noticing that the other party took too long to answer
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) enum Event {
     Initialize,
     ReceivedMessage(Vec<u8>),
+    Timeout,
 }
 
 /// Outcome of processing an event: either no action or a message to send to the other party
@@ -68,6 +76,7 @@ pub(super) struct CommonStateMachine {
     pub(super) credentials: Vec<CredentialAndPurposeKey>,
     pub(super) trust_policy: Arc<dyn TrustPolicy>,
     pub(super) trust_context: Option<TrustContext>,
+    pub(super) handshake_timeout: Duration,
     their_identifier: Option<Identifier>,
 }
 
@@ -79,6 +88,7 @@ impl CommonStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        handshake_timeout: Option<Duration>,
     ) -> Self {
         Self {
             identities,
@@ -87,10 +97,24 @@ impl CommonStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            handshake_timeout: handshake_timeout.unwrap_or(DEFAULT_HANDSHAKE_TIMEOUT),
             their_identifier: None,
         }
     }
 
+    /// Return the error failing a handshake which was not completed before its timeout
+    pub(super) fn timeout_error(role: Role, status: &Status) -> Error {
+        Error::new(
+            Origin::Channel,
+            Kind::Timeout,
+            format!(
+                "The {} handshake timed out in the state {:?}",
+                role.str(),
+                status
+            ),
+        )
+    }
+
     /// Prepare a payload containing the identity of the current party and serialize it.
     /// That payload contains:
     ///
@@ -172,6 +196,10 @@ impl CommonStateMachine {
         let trusted = self.trust_policy.check(&trust_info).await?;
         if !trusted {
             // TODO: Shutdown? Communicate error?
+            warn!(
+                "the identity {} was rejected by the secure channel trust policy",
+                their_identifier
+            );
             return Err(IdentityError::SecureChannelTrustCheckFailed.into());
         }
         debug!(
@@ -197,7 +225,12 @@ impl CommonStateMachine {
                     .await;
 
                 if let Some(err) = result.err() {
-                    warn!("a credential could not be validated {}", err.to_string());
+                    warn!(
+                        "a credential presented by the identity {} could not be validated with the trust context {}: {}",
+                        their_identifier,
+                        trust_context.id(),
+                        err.to_string()
+                    );
                     // TODO: consider the possibility of keep going when a credential validation fails
                     return Err(
                         IdentityError::SecureChannelVerificationFailedIncorrectCredential.into(),
@@ -245,3 +278,114 @@ pub(super) struct IdentityAndCredentials {
     /// to verify those Credentials
     #[n(3)] pub(super) credentials: Vec<CredentialAndPurposeKey>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CredentialSchemaIdentifier;
+    use crate::{
+        identities, Attributes, AuthorityService, TrustEveryonePolicy, TrustIdentifierPolicy,
+    };
+    use core::time::Duration;
+    use minicbor::bytes::ByteVec;
+    use ockam_core::compat::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn test_reject_identity_with_trust_policy() -> Result<()> {
+        let identities = identities();
+        let creation = identities.identities_creation();
+        let trusted = creation.create_identity().await?;
+        let untrusted = creation.create_identity().await?;
+
+        let trust_policy = Arc::new(TrustIdentifierPolicy::new(trusted.identifier().clone()));
+        let state_machine = create_state_machine(identities, trust_policy, None).await?;
+
+        let error = state_machine
+            .verify_credentials(untrusted.identifier(), vec![])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            identity_error(&error),
+            Some(IdentityError::SecureChannelTrustCheckFailed)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_invalid_credential() -> Result<()> {
+        let identities = identities();
+        let creation = identities.identities_creation();
+        let authority = creation.create_identity().await?;
+        let other_issuer = creation.create_identity().await?;
+        let subject = creation.create_identity().await?;
+
+        // the credential is not issued by the authority of the trust context
+        let mut map: BTreeMap<ByteVec, ByteVec> = Default::default();
+        map.insert(b"key".to_vec().into(), b"value".to_vec().into());
+        let credential = identities
+            .credentials()
+            .credentials_creation()
+            .issue_credential(
+                other_issuer.identifier(),
+                subject.identifier(),
+                Attributes {
+                    schema: CredentialSchemaIdentifier(1),
+                    map,
+                },
+                Duration::from_secs(60),
+            )
+            .await?;
+        let trust_context = TrustContext::new(
+            "trust_context_id".to_string(),
+            Some(AuthorityService::new(
+                identities.credentials(),
+                authority.identifier().clone(),
+                None,
+            )),
+        );
+        let state_machine = create_state_machine(
+            identities,
+            Arc::new(TrustEveryonePolicy),
+            Some(trust_context),
+        )
+        .await?;
+
+        let error = state_machine
+            .verify_credentials(subject.identifier(), vec![credential])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            identity_error(&error),
+            Some(IdentityError::SecureChannelVerificationFailedIncorrectCredential)
+        ));
+        Ok(())
+    }
+
+    /// Return the identity error at the origin of an error, if there is one
+    fn identity_error(error: &Error) -> Option<&IdentityError> {
+        use ockam_core::compat::error::Error as _;
+        error.source()?.downcast_ref::<IdentityError>()
+    }
+
+    async fn create_state_machine(
+        identities: Arc<Identities>,
+        trust_policy: Arc<dyn TrustPolicy>,
+        trust_context: Option<TrustContext>,
+    ) -> Result<CommonStateMachine> {
+        let identity = identities.identities_creation().create_identity().await?;
+        let purpose_key = identities
+            .purpose_keys()
+            .purpose_keys_creation()
+            .create_secure_channel_purpose_key(identity.identifier())
+            .await?;
+        Ok(CommonStateMachine::new(
+            identities,
+            identity.identifier().clone(),
+            purpose_key.attestation().clone(),
+            vec![],
+            trust_policy,
+            trust_context,
+            None,
+        ))
+    }
+}
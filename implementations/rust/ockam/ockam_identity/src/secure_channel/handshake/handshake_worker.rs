@@ -3,13 +3,13 @@ use core::time::Duration;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{
-    AllowAll, Any, Decodable, DenyAll, Error, Mailbox, Mailboxes, OutgoingAccessControl, Route,
-    Routed,
+    Address, AllowAll, AllowSourceAddress, Any, Decodable, DenyAll, Error, Mailbox, Mailboxes,
+    OutgoingAccessControl, Route, Routed,
 };
 use ockam_core::{AllowOnwardAddress, Result, Worker};
 use ockam_node::callback::CallbackSender;
-use ockam_node::{Context, WorkerBuilder};
-use tracing::{debug, info};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
+use tracing::{debug, info, warn};
 
 use crate::models::{CredentialAndPurposeKey, Identifier};
 use crate::secure_channel::decryptor::DecryptorHandler;
@@ -17,12 +17,13 @@ use crate::secure_channel::encryptor::Encryptor;
 use crate::secure_channel::encryptor_worker::EncryptorWorker;
 use crate::secure_channel::handshake::handshake_state_machine::Action::SendMessage;
 use crate::secure_channel::handshake::handshake_state_machine::Event::{
-    Initialize, ReceivedMessage,
+    Initialize, ReceivedMessage, Timeout,
 };
 use crate::secure_channel::handshake::handshake_state_machine::{
     Action, HandshakeResults, StateMachine,
 };
 use crate::secure_channel::handshake::initiator_state_machine::InitiatorStateMachine;
+use crate::secure_channel::handshake::observer::HandshakeObserver;
 use crate::secure_channel::handshake::responder_state_machine::ResponderStateMachine;
 use crate::secure_channel::{Addresses, Role};
 use crate::{
@@ -41,6 +42,8 @@ pub(crate) struct HandshakeWorker {
     role: Role,
     remote_route: Option<Route>,
     decryptor_handler: Option<DecryptorHandler>,
+    /// sends a message to this worker when the handshake takes too long
+    timeout_event: Option<DelayedEvent<Vec<u8>>>,
 }
 
 #[ockam_core::worker]
@@ -51,6 +54,12 @@ impl Worker for HandshakeWorker {
     /// Initialize the state machine with an `Initialize` event
     /// Depending on the state machine role there might be a message to send to the other party
     async fn initialize(&mut self, context: &mut Self::Context) -> Result<()> {
+        if let Some(timeout_event) = self.timeout_event.as_mut() {
+            timeout_event
+                .schedule(self.state_machine.handshake_timeout())
+                .await?;
+        }
+
         match self.state_machine.on_event(Initialize).await? {
             SendMessage(message) => {
                 debug!(
@@ -80,6 +89,10 @@ impl Worker for HandshakeWorker {
         context: &mut Self::Context,
         message: Routed<Self::Message>,
     ) -> Result<()> {
+        if message.msg_addr() == self.addresses.handshake_timeout {
+            return self.handle_timeout(context).await;
+        }
+
         // Once the decryptor has been initialized, let it handle messages
         // Some messages can come from other systems using the remote address
         // and some messages can come from the current node when the decryptor
@@ -122,6 +135,9 @@ impl Worker for HandshakeWorker {
 
         // if we reached the final state we can make a pair of encryptor/decryptor
         if let Some(final_state) = self.state_machine.get_handshake_results() {
+            // the handshake is complete, there is no need to be notified of a timeout anymore
+            self.timeout_event = None;
+
             // start the encryptor worker and return the decryptor
             self.decryptor_handler = Some(self.finalize(context, final_state).await?);
             if let Some(callback_sender) = self.callback_sender.take() {
@@ -161,6 +177,7 @@ impl HandshakeWorker {
         trust_context: Option<TrustContext>,
         remote_route: Option<Route>,
         timeout: Option<Duration>,
+        observer: Option<Arc<dyn HandshakeObserver>>,
         role: Role,
     ) -> Result<()> {
         let vault = secure_channels.identities.vault().secure_channel_vault;
@@ -175,6 +192,7 @@ impl HandshakeWorker {
                     credentials,
                     trust_policy,
                     trust_context,
+                    timeout,
                 )
                 .await?,
             )
@@ -188,6 +206,8 @@ impl HandshakeWorker {
                     credentials,
                     trust_policy,
                     trust_context,
+                    timeout,
+                    observer,
                 )
                 .await?,
             )
@@ -200,6 +220,10 @@ impl HandshakeWorker {
             (None, None)
         };
 
+        let timeout_event =
+            DelayedEvent::create(context, addresses.handshake_timeout.clone(), vec![]).await?;
+        let timeout_source_address = timeout_event.address();
+
         let worker = Self {
             secure_channels,
             callback_sender,
@@ -209,12 +233,14 @@ impl HandshakeWorker {
             remote_route: remote_route.clone(),
             addresses: addresses.clone(),
             decryptor_handler: None,
+            timeout_event: Some(timeout_event),
         };
 
         WorkerBuilder::new(worker)
             .with_mailboxes(Self::create_mailboxes(
                 &addresses,
                 decryptor_outgoing_access_control,
+                timeout_source_address,
             ))
             .start(context)
             .await?;
@@ -241,6 +267,27 @@ impl HandshakeWorker {
         Ok(())
     }
 
+    /// Send a `Timeout` event to the state machine if the handshake is not finished yet.
+    /// If the state machine fails the handshake, this worker is stopped
+    async fn handle_timeout(&mut self, context: &mut Context) -> Result<()> {
+        if self.decryptor_handler.is_some() {
+            return Ok(());
+        }
+
+        if let Err(e) = self.state_machine.on_event(Timeout).await {
+            warn!(
+                "The {} handshake at {} failed: {}",
+                self.role.str(),
+                self.addresses.decryptor_remote,
+                e
+            );
+            context
+                .stop_worker(self.addresses.decryptor_remote.clone())
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Return the route for the other party's handshake worker
     fn remote_route(&self) -> Result<Route> {
         self.remote_route.clone().ok_or_else(|| {
@@ -256,6 +303,7 @@ impl HandshakeWorker {
     pub(crate) fn create_mailboxes(
         addresses: &Addresses,
         decryptor_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+        timeout_source_address: Address,
     ) -> Mailboxes {
         let remote_mailbox = Mailbox::new(
             addresses.decryptor_remote.clone(),
@@ -277,7 +325,17 @@ impl HandshakeWorker {
             Arc::new(AllowAll),
         );
 
-        Mailboxes::new(remote_mailbox, vec![internal_mailbox, api_mailbox])
+        // Only accept timeout notifications from our own delayed event
+        let timeout_mailbox = Mailbox::new(
+            addresses.handshake_timeout.clone(),
+            Arc::new(AllowSourceAddress(timeout_source_address)),
+            Arc::new(DenyAll),
+        );
+
+        Mailboxes::new(
+            remote_mailbox,
+            vec![internal_mailbox, api_mailbox, timeout_mailbox],
+        )
     }
 
     /// Finalize the handshake by creating a `Decryptor` and an `EncryptorWorker`
@@ -360,3 +418,80 @@ impl HandshakeWorker {
         Ok(decryptor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure_channels::secure_channels;
+    use crate::TrustEveryonePolicy;
+    use ockam_core::route;
+
+    #[ockam_macros::test]
+    async fn test_stop_the_responder_when_the_initiator_stops_after_message1(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let secure_channels = secure_channels();
+        let identities = secure_channels.identities();
+        let initiator = identities.identities_creation().create_identity().await?;
+        let responder = identities.identities_creation().create_identity().await?;
+
+        let mut initiator_state_machine = InitiatorStateMachine::new(
+            identities.vault().secure_channel_vault,
+            identities.clone(),
+            initiator.identifier().clone(),
+            create_purpose_key(&secure_channels, initiator.identifier()).await?,
+            vec![],
+            Arc::new(TrustEveryonePolicy),
+            None,
+            None,
+        )
+        .await?;
+        let message1 = match initiator_state_machine.on_event(Initialize).await? {
+            SendMessage(message) => message,
+            Action::NoAction => panic!("the initiator should send message 1"),
+        };
+
+        let addresses = Addresses::generate(Role::Responder);
+        HandshakeWorker::create(
+            ctx,
+            secure_channels.clone(),
+            addresses.clone(),
+            responder.identifier().clone(),
+            create_purpose_key(&secure_channels, responder.identifier()).await?,
+            Arc::new(TrustEveryonePolicy),
+            Arc::new(AllowAll),
+            vec![],
+            None,
+            None,
+            Some(Duration::from_millis(200)),
+            None,
+            Role::Responder,
+        )
+        .await?;
+
+        // the responder answers message 1 but never gets message 3
+        ctx.send(route![addresses.decryptor_remote.clone()], message1)
+            .await?;
+        ctx.receive::<Vec<u8>>().await?;
+
+        ctx.sleep(Duration::from_millis(500)).await;
+        assert!(!ctx
+            .list_workers()
+            .await?
+            .contains(&addresses.decryptor_remote));
+
+        ctx.stop().await
+    }
+
+    async fn create_purpose_key(
+        secure_channels: &SecureChannels,
+        identifier: &Identifier,
+    ) -> Result<SecureChannelPurposeKey> {
+        secure_channels
+            .identities()
+            .purpose_keys()
+            .purpose_keys_creation()
+            .create_secure_channel_purpose_key(identifier)
+            .await
+    }
+}
@@ -1,3 +1,4 @@
+use core::time::Duration;
 use delegate::delegate;
 use ockam_core::async_trait;
 use ockam_core::compat::sync::Arc;
@@ -49,6 +50,12 @@ impl StateMachine for InitiatorStateMachine {
                 self.set_final_state(Initiator).await?;
                 Ok(SendMessage(message3))
             }
+            // Fail the handshake if the other party did not send message 2 before the timeout
+            (s @ WaitingForMessage2, Timeout) => {
+                Err(CommonStateMachine::timeout_error(Initiator, &s))
+            }
+            // The handshake was completed before the timeout
+            (Ready(_), Timeout) => Ok(NoAction),
             // incorrect state / event
             (s, e) => Err(Error::new(
                 Origin::Channel,
@@ -64,6 +71,10 @@ impl StateMachine for InitiatorStateMachine {
     fn get_handshake_results(&self) -> Option<HandshakeResults> {
         self.make_handshake_results(self.get_handshake_keys())
     }
+
+    fn handshake_timeout(&self) -> Duration {
+        self.common.handshake_timeout
+    }
 }
 
 /// Implementation of the state machine actions, delegated to the Handshake module
@@ -103,6 +114,7 @@ impl InitiatorStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        handshake_timeout: Option<Duration>,
     ) -> Result<InitiatorStateMachine> {
         let common = CommonStateMachine::new(
             identities,
@@ -111,6 +123,7 @@ impl InitiatorStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            handshake_timeout,
         );
         let identity_payload = common.make_identity_payload().await?;
 
@@ -121,3 +134,52 @@ impl InitiatorStateMachine {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{identities, TrustEveryonePolicy};
+
+    #[tokio::test]
+    async fn test_timeout_waiting_for_message2() -> Result<()> {
+        let mut initiator = create_initiator().await?;
+
+        let action = initiator.on_event(Initialize).await?;
+        assert!(matches!(action, SendMessage(_)));
+
+        let error = initiator.on_event(Timeout).await.unwrap_err();
+        assert_eq!(error.code().kind, Kind::Timeout);
+        assert!(initiator.get_handshake_results().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_timeout_before_initialization() -> Result<()> {
+        let mut initiator = create_initiator().await?;
+
+        let error = initiator.on_event(Timeout).await.unwrap_err();
+        assert_eq!(error.code().kind, Kind::Invalid);
+        Ok(())
+    }
+
+    async fn create_initiator() -> Result<InitiatorStateMachine> {
+        let identities = identities();
+        let identity = identities.identities_creation().create_identity().await?;
+        let purpose_key = identities
+            .purpose_keys()
+            .purpose_keys_creation()
+            .create_secure_channel_purpose_key(identity.identifier())
+            .await?;
+        InitiatorStateMachine::new(
+            identities.vault().secure_channel_vault,
+            identities,
+            identity.identifier().clone(),
+            purpose_key,
+            vec![],
+            Arc::new(TrustEveryonePolicy),
+            None,
+            None,
+        )
+        .await
+    }
+}
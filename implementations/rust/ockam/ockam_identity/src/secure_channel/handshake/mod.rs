@@ -9,4 +9,7 @@ mod handshake;
 mod handshake_state_machine;
 pub(crate) mod handshake_worker;
 mod initiator_state_machine;
+mod observer;
 mod responder_state_machine;
+
+pub use observer::*;
@@ -0,0 +1,46 @@
+use ockam_core::Error;
+
+use crate::secure_channel::handshake::handshake_state_machine::Status;
+
+/// Stage of a secure channel handshake, as seen by an [`HandshakeObserver`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeStage {
+    /// The handshake has not started yet
+    Initial,
+    /// Waiting for the first message of the initiator
+    WaitingForMessage1,
+    /// Waiting for the first message of the responder
+    WaitingForMessage2,
+    /// Waiting for the second message of the initiator
+    WaitingForMessage3,
+    /// The handshake is complete
+    Ready,
+}
+
+impl HandshakeStage {
+    pub(super) fn from_status(status: &Status) -> Self {
+        match status {
+            Status::Initial => HandshakeStage::Initial,
+            Status::WaitingForMessage1 => HandshakeStage::WaitingForMessage1,
+            Status::WaitingForMessage2 => HandshakeStage::WaitingForMessage2,
+            Status::WaitingForMessage3 => HandshakeStage::WaitingForMessage3,
+            Status::Ready(_) => HandshakeStage::Ready,
+        }
+    }
+}
+
+/// Observer of the handshakes performed by a secure channel listener.
+///
+/// It can be used to collect metrics, for example the time spent waiting for a message
+/// or the number of identities which could not be verified.
+/// All the methods do nothing by default.
+pub trait HandshakeObserver: Send + Sync + 'static {
+    /// The handshake moved from one stage to another
+    fn on_transition(&self, _from: HandshakeStage, _to: HandshakeStage) {}
+
+    /// The identity presented by the other party could not be verified
+    fn on_identity_verification_failed(&self, _error: &Error) {}
+
+    /// The handshake failed while being in the given stage
+    fn on_failure(&self, _stage: HandshakeStage, _error: &Error) {}
+}
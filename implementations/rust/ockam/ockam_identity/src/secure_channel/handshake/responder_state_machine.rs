@@ -1,10 +1,12 @@
 use async_trait::async_trait;
+use core::time::Duration;
 use delegate::delegate;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_vault::{VaultForSecureChannels, X25519PublicKey};
+use tracing::debug;
 use Action::*;
 use Event::*;
 use Role::*;
@@ -17,12 +19,46 @@ use crate::secure_channel::handshake::handshake_state_machine::{
     Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, IdentityAndCredentials,
     StateMachine, Status,
 };
+use crate::secure_channel::handshake::observer::{HandshakeObserver, HandshakeStage};
 use crate::{Identities, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy};
 
 /// Implementation of a state machine for the key exchange on the responder side
 #[async_trait]
 impl StateMachine for ResponderStateMachine {
     async fn on_event(&mut self, event: Event) -> Result<Action> {
+        let from = self.stage();
+        let result = self.process_event(event).await;
+        let to = self.stage();
+        match &result {
+            Ok(_) if from != to => {
+                debug!("responder handshake transition {:?} -> {:?}", from, to);
+                if let Some(observer) = &self.observer {
+                    observer.on_transition(from, to);
+                }
+            }
+            Err(e) => {
+                debug!("responder handshake failure in the stage {:?}: {}", from, e);
+                if let Some(observer) = &self.observer {
+                    observer.on_failure(from, e);
+                }
+            }
+            _ => (),
+        }
+        result
+    }
+
+    fn get_handshake_results(&self) -> Option<HandshakeResults> {
+        self.make_handshake_results(self.get_handshake_keys())
+    }
+
+    fn handshake_timeout(&self) -> Duration {
+        self.common.handshake_timeout
+    }
+}
+
+impl ResponderStateMachine {
+    /// Perform the transition corresponding to an event in the current state
+    async fn process_event(&mut self, event: Event) -> Result<Action> {
         let state = self.handshake.state.clone();
         match (state.status, event) {
             // Initialize the handshake and wait for message 1
@@ -48,11 +84,25 @@ impl StateMachine for ResponderStateMachine {
                 let message3_payload = self.decode_message3(&message).await?;
                 let their_identity_payload: IdentityAndCredentials =
                     minicbor::decode(&message3_payload)?;
-                self.verify_identity(their_identity_payload, &self.handshake.state.rs()?.clone())
-                    .await?;
+                let their_public_key = self.handshake.state.rs()?.clone();
+                if let Err(e) = self
+                    .verify_identity(their_identity_payload, &their_public_key)
+                    .await
+                {
+                    if let Some(observer) = &self.observer {
+                        observer.on_identity_verification_failed(&e);
+                    }
+                    return Err(e);
+                }
                 self.set_final_state(Responder).await?;
                 Ok(NoAction)
             }
+            // Fail the handshake if the other party did not send a message before the timeout
+            (s @ (WaitingForMessage1 | WaitingForMessage3), Timeout) => {
+                Err(CommonStateMachine::timeout_error(Responder, &s))
+            }
+            // The handshake was completed before the timeout
+            (Ready(_), Timeout) => Ok(NoAction),
             // incorrect state / event
             (s, e) => Err(Error::new(
                 Origin::Channel,
@@ -64,10 +114,6 @@ impl StateMachine for ResponderStateMachine {
             )),
         }
     }
-
-    fn get_handshake_results(&self) -> Option<HandshakeResults> {
-        self.make_handshake_results(self.get_handshake_keys())
-    }
 }
 
 pub struct ResponderStateMachine {
@@ -75,6 +121,8 @@ pub struct ResponderStateMachine {
     handshake: Handshake,
     /// this serialized payload contains an identity, its credentials and a signature of its static key
     identity_payload: Option<Vec<u8>>,
+    /// optional observer of the handshake transitions
+    observer: Option<Arc<dyn HandshakeObserver>>,
 }
 
 impl ResponderStateMachine {
@@ -98,6 +146,7 @@ impl ResponderStateMachine {
 }
 
 impl ResponderStateMachine {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         vault: Arc<dyn VaultForSecureChannels>,
         identities: Arc<Identities>,
@@ -106,7 +155,21 @@ impl ResponderStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        handshake_timeout: Option<Duration>,
+        observer: Option<Arc<dyn HandshakeObserver>>,
     ) -> Result<ResponderStateMachine> {
+        if let Some(trust_context) = &trust_context {
+            if trust_context.requires_credentials() && credentials.is_empty() {
+                return Err(Error::new(
+                    Origin::Channel,
+                    Kind::Invalid,
+                    format!(
+                        "The trust context {} requires credentials but none were provided",
+                        trust_context.id()
+                    ),
+                ));
+            }
+        }
         let common = CommonStateMachine::new(
             identities,
             identifier,
@@ -114,6 +177,7 @@ impl ResponderStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            handshake_timeout,
         );
         let identity_payload = common.make_identity_payload().await?;
 
@@ -121,6 +185,254 @@ impl ResponderStateMachine {
             common,
             handshake: Handshake::new(vault, purpose_key.key().clone()).await?,
             identity_payload: Some(identity_payload),
+            observer,
         })
     }
+
+    fn stage(&self) -> HandshakeStage {
+        HandshakeStage::from_status(&self.handshake.state.status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CredentialSchemaIdentifier;
+    use crate::secure_channel::handshake::initiator_state_machine::InitiatorStateMachine;
+    use crate::{
+        identities, Attributes, AuthorityService, CredentialsMemoryRetriever, TrustEveryonePolicy,
+        TrustIdentifierPolicy,
+    };
+    use minicbor::bytes::ByteVec;
+    use ockam_core::compat::collections::BTreeMap;
+    use ockam_core::compat::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_timeout_waiting_for_message3() -> Result<()> {
+        let identities = identities();
+        let mut initiator = create_initiator(identities.clone()).await?;
+        let mut responder = create_responder(identities).await?;
+
+        assert_eq!(responder.on_event(Initialize).await?, NoAction);
+        let message1 = match initiator.on_event(Initialize).await? {
+            SendMessage(message) => message,
+            NoAction => panic!("the initiator should send message 1"),
+        };
+        let action = responder.on_event(ReceivedMessage(message1)).await?;
+        assert!(matches!(action, SendMessage(_)));
+
+        let error = responder.on_event(Timeout).await.unwrap_err();
+        assert_eq!(error.code().kind, Kind::Timeout);
+        assert!(responder.get_handshake_results().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_timeout_after_handshake() -> Result<()> {
+        let identities = identities();
+        let mut initiator = create_initiator(identities.clone()).await?;
+        let mut responder = create_responder(identities).await?;
+
+        perform_handshake(&mut initiator, &mut responder).await?;
+        assert_eq!(responder.on_event(Timeout).await?, NoAction);
+        assert!(responder.get_handshake_results().is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_observe_transitions() -> Result<()> {
+        let identities = identities();
+        let observer = Arc::new(RecordingObserver::default());
+        let mut initiator = create_initiator(identities.clone()).await?;
+        let mut responder = create_observed_responder(
+            identities,
+            Arc::new(TrustEveryonePolicy),
+            Some(observer.clone()),
+        )
+        .await?;
+
+        perform_handshake(&mut initiator, &mut responder).await?;
+        assert!(responder.get_handshake_results().is_some());
+
+        use HandshakeStage as S;
+        assert_eq!(
+            *observer.transitions.lock().unwrap(),
+            vec![
+                (S::Initial, S::WaitingForMessage1),
+                (S::WaitingForMessage1, S::WaitingForMessage3),
+                (S::WaitingForMessage3, S::Ready),
+            ]
+        );
+        assert!(observer.failures.lock().unwrap().is_empty());
+        assert_eq!(*observer.failed_verifications.lock().unwrap(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_observe_failed_identity_verification() -> Result<()> {
+        let identities = identities();
+        let other = identities.identities_creation().create_identity().await?;
+        let observer = Arc::new(RecordingObserver::default());
+        let mut initiator = create_initiator(identities.clone()).await?;
+        let mut responder = create_observed_responder(
+            identities,
+            Arc::new(TrustIdentifierPolicy::new(other.identifier().clone())),
+            Some(observer.clone()),
+        )
+        .await?;
+
+        assert!(perform_handshake(&mut initiator, &mut responder)
+            .await
+            .is_err());
+        assert_eq!(*observer.failed_verifications.lock().unwrap(), 1);
+        assert_eq!(
+            *observer.failures.lock().unwrap(),
+            vec![HandshakeStage::WaitingForMessage3]
+        );
+        Ok(())
+    }
+
+    /// Exchange the 3 messages of the handshake
+    async fn perform_handshake(
+        initiator: &mut InitiatorStateMachine,
+        responder: &mut ResponderStateMachine,
+    ) -> Result<()> {
+        responder.on_event(Initialize).await?;
+        let message1 = match initiator.on_event(Initialize).await? {
+            SendMessage(message) => message,
+            NoAction => panic!("the initiator should send message 1"),
+        };
+        let message2 = match responder.on_event(ReceivedMessage(message1)).await? {
+            SendMessage(message) => message,
+            NoAction => panic!("the responder should send message 2"),
+        };
+        let message3 = match initiator.on_event(ReceivedMessage(message2)).await? {
+            SendMessage(message) => message,
+            NoAction => panic!("the initiator should send message 3"),
+        };
+        responder.on_event(ReceivedMessage(message3)).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_credentials() -> Result<()> {
+        let identities = identities();
+        let creation = identities.identities_creation();
+        let authority = creation.create_identity().await?;
+        let identity = creation.create_identity().await?;
+        let purpose_key = identities
+            .purpose_keys()
+            .purpose_keys_creation()
+            .create_secure_channel_purpose_key(identity.identifier())
+            .await?;
+
+        // the trust context provides a credential for the responder identity
+        let credential = identities
+            .credentials()
+            .credentials_creation()
+            .issue_credential(
+                authority.identifier(),
+                identity.identifier(),
+                Attributes {
+                    schema: CredentialSchemaIdentifier(1),
+                    map: BTreeMap::<ByteVec, ByteVec>::new(),
+                },
+                Duration::from_secs(60),
+            )
+            .await?;
+        let trust_context = TrustContext::new(
+            "trust_context_id".to_string(),
+            Some(AuthorityService::new(
+                identities.credentials(),
+                authority.identifier().clone(),
+                Some(Arc::new(CredentialsMemoryRetriever::new(credential))),
+            )),
+        );
+
+        let result = ResponderStateMachine::new(
+            identities.vault().secure_channel_vault,
+            identities,
+            identity.identifier().clone(),
+            purpose_key,
+            vec![],
+            Arc::new(TrustEveryonePolicy),
+            Some(trust_context),
+            None,
+            None,
+        )
+        .await;
+        let error = result.err().expect("credentials should be required");
+        assert_eq!(error.code().kind, Kind::Invalid);
+        Ok(())
+    }
+
+    async fn create_initiator(identities: Arc<Identities>) -> Result<InitiatorStateMachine> {
+        let identity = identities.identities_creation().create_identity().await?;
+        let purpose_key = identities
+            .purpose_keys()
+            .purpose_keys_creation()
+            .create_secure_channel_purpose_key(identity.identifier())
+            .await?;
+        InitiatorStateMachine::new(
+            identities.vault().secure_channel_vault,
+            identities,
+            identity.identifier().clone(),
+            purpose_key,
+            vec![],
+            Arc::new(TrustEveryonePolicy),
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn create_responder(identities: Arc<Identities>) -> Result<ResponderStateMachine> {
+        create_observed_responder(identities, Arc::new(TrustEveryonePolicy), None).await
+    }
+
+    async fn create_observed_responder(
+        identities: Arc<Identities>,
+        trust_policy: Arc<dyn TrustPolicy>,
+        observer: Option<Arc<dyn HandshakeObserver>>,
+    ) -> Result<ResponderStateMachine> {
+        let identity = identities.identities_creation().create_identity().await?;
+        let purpose_key = identities
+            .purpose_keys()
+            .purpose_keys_creation()
+            .create_secure_channel_purpose_key(identity.identifier())
+            .await?;
+        ResponderStateMachine::new(
+            identities.vault().secure_channel_vault,
+            identities,
+            identity.identifier().clone(),
+            purpose_key,
+            vec![],
+            trust_policy,
+            None,
+            None,
+            observer,
+        )
+        .await
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        transitions: Mutex<Vec<(HandshakeStage, HandshakeStage)>>,
+        failures: Mutex<Vec<HandshakeStage>>,
+        failed_verifications: Mutex<usize>,
+    }
+
+    impl HandshakeObserver for RecordingObserver {
+        fn on_transition(&self, from: HandshakeStage, to: HandshakeStage) {
+            self.transitions.lock().unwrap().push((from, to));
+        }
+
+        fn on_identity_verification_failed(&self, _error: &Error) {
+            *self.failed_verifications.lock().unwrap() += 1;
+        }
+
+        fn on_failure(&self, stage: HandshakeStage, _error: &Error) {
+            self.failures.lock().unwrap().push(stage);
+        }
+    }
 }
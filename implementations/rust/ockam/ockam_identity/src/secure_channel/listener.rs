@@ -110,6 +110,7 @@ impl Worker for IdentityChannelListener {
             self.options.trust_context.clone(),
             None,
             None,
+            self.options.handshake_observer.clone(),
             Role::Responder,
         )
         .await?;
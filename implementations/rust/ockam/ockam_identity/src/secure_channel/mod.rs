@@ -20,6 +20,7 @@ pub use access_control::*;
 pub(crate) use addresses::*;
 pub use api::*;
 pub(crate) use handshake::*;
+pub use handshake::{HandshakeObserver, HandshakeStage};
 pub(crate) use listener::*;
 pub use local_info::*;
 pub use options::*;
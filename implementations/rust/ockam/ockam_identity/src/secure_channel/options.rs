@@ -4,7 +4,7 @@ use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl,
 use ockam_core::{Address, OutgoingAccessControl, Result};
 
 use crate::models::CredentialAndPurposeKey;
-use crate::secure_channel::Addresses;
+use crate::secure_channel::{Addresses, HandshakeObserver};
 use crate::{TrustContext, TrustEveryonePolicy, TrustPolicy};
 
 use core::fmt;
@@ -130,6 +130,7 @@ pub struct SecureChannelListenerOptions {
     pub(crate) trust_policy: Arc<dyn TrustPolicy>,
     pub(crate) trust_context: Option<TrustContext>,
     pub(crate) credentials: Vec<CredentialAndPurposeKey>,
+    pub(crate) handshake_observer: Option<Arc<dyn HandshakeObserver>>,
 }
 
 impl fmt::Debug for SecureChannelListenerOptions {
@@ -150,6 +151,7 @@ impl SecureChannelListenerOptions {
             trust_policy: Arc::new(TrustEveryonePolicy),
             trust_context: None,
             credentials: vec![],
+            handshake_observer: None,
         }
     }
 
@@ -186,6 +188,12 @@ impl SecureChannelListenerOptions {
         self
     }
 
+    /// Set an observer for the handshakes of the spawned secure channels
+    pub fn with_handshake_observer(mut self, observer: impl HandshakeObserver) -> Self {
+        self.handshake_observer = Some(Arc::new(observer));
+        self
+    }
+
     /// Freshly generated [`FlowControlId`]
     pub fn spawner_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
@@ -1,5 +1,7 @@
 use ockam_core::async_trait;
 use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_core::{AsyncTryClone, Result};
 
 use crate::secure_channel::trust_policy::{SecureChannelTrustInfo, TrustPolicy};
@@ -27,6 +29,31 @@ impl<F: TrustPolicy, S: TrustPolicy> TrustPolicy for AllTrustPolicy<F, S> {
     }
 }
 
+/// Succeeds only if all the `TrustPolicy` checks succeed.
+/// The checks stop at the first policy which does not succeed.
+/// An empty list of policies succeeds
+#[derive(Clone)]
+pub struct AllOf(pub Vec<Arc<dyn TrustPolicy>>);
+
+impl AllOf {
+    /// Constructor
+    pub fn new(policies: Vec<Arc<dyn TrustPolicy>>) -> Self {
+        AllOf(policies)
+    }
+}
+
+#[async_trait]
+impl TrustPolicy for AllOf {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        for policy in &self.0 {
+            if !policy.check(trust_info).await? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::models::Identifier;
@@ -70,4 +97,35 @@ mod test {
             .await
             .unwrap());
     }
+
+    #[tokio::test]
+    async fn test_all_of() {
+        use crate::secure_channel::trust_policy::test_utils::CountingTrustPolicyStub;
+        use crate::secure_channel::trust_policy::{AllOf, AnyOf};
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        use ockam_core::compat::sync::Arc;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let stub = |result: bool| CountingTrustPolicyStub::create(result, calls.clone());
+
+        let id = Identifier::try_from("Iabababababababababababababababababababab").unwrap();
+        let trust_info = SecureChannelTrustInfo::new(id);
+        // all the policies pass
+        let policy = AllOf::new(vec![stub(true), stub(true), stub(true)]);
+        assert!(policy.check(&trust_info).await.unwrap());
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 3);
+
+        // the checks stop at the first failure
+        let policy = AllOf::new(vec![stub(true), stub(false), stub(true)]);
+        assert!(!policy.check(&trust_info).await.unwrap());
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 2);
+
+        // mixed with an AnyOf policy
+        let any_of: Arc<dyn TrustPolicy> = Arc::new(AnyOf::new(vec![stub(false), stub(true)]));
+        let policy = AllOf::new(vec![any_of, stub(true)]);
+        assert!(policy.check(&trust_info).await.unwrap());
+
+        // an empty list of policies passes
+        assert!(AllOf::new(vec![]).check(&trust_info).await.unwrap());
+    }
 }
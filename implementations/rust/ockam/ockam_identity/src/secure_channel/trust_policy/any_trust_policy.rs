@@ -1,5 +1,7 @@
 use ockam_core::async_trait;
 use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_core::{AsyncTryClone, Result};
 
 use crate::secure_channel::trust_policy::{SecureChannelTrustInfo, TrustPolicy};
@@ -28,6 +30,31 @@ impl<F: TrustPolicy, S: TrustPolicy> TrustPolicy for AnyTrustPolicy<F, S> {
     }
 }
 
+/// Succeeds if any of the `TrustPolicy` checks succeeds.
+/// The checks stop at the first policy which succeeds.
+/// An empty list of policies fails
+#[derive(Clone)]
+pub struct AnyOf(pub Vec<Arc<dyn TrustPolicy>>);
+
+impl AnyOf {
+    /// Constructor
+    pub fn new(policies: Vec<Arc<dyn TrustPolicy>>) -> Self {
+        AnyOf(policies)
+    }
+}
+
+#[async_trait]
+impl TrustPolicy for AnyOf {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        for policy in &self.0 {
+            if policy.check(trust_info).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::models::Identifier;
@@ -71,4 +98,35 @@ mod test {
             .await
             .unwrap());
     }
+
+    #[tokio::test]
+    async fn test_any_of() {
+        use crate::secure_channel::trust_policy::test_utils::CountingTrustPolicyStub;
+        use crate::secure_channel::trust_policy::{AllOf, AnyOf};
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        use ockam_core::compat::sync::Arc;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let stub = |result: bool| CountingTrustPolicyStub::create(result, calls.clone());
+
+        let id = Identifier::try_from("Iabababababababababababababababababababab").unwrap();
+        let trust_info = SecureChannelTrustInfo::new(id);
+        // any of the policies passes
+        let policy = AnyOf::new(vec![stub(false), stub(true), stub(false)]);
+        assert!(policy.check(&trust_info).await.unwrap());
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 2);
+
+        // no policy passes
+        let policy = AnyOf::new(vec![stub(false), stub(false)]);
+        assert!(!policy.check(&trust_info).await.unwrap());
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 2);
+
+        // mixed with an AllOf policy
+        let all_of: Arc<dyn TrustPolicy> = Arc::new(AllOf::new(vec![stub(true), stub(false)]));
+        let policy = AnyOf::new(vec![all_of, stub(true)]);
+        assert!(policy.check(&trust_info).await.unwrap());
+
+        // an empty list of policies fails
+        assert!(!AnyOf::new(vec![]).check(&trust_info).await.unwrap());
+    }
 }
@@ -11,3 +11,31 @@ pub use trust_everyone_policy::*;
 pub use trust_identifier_policy::*;
 pub use trust_multi_identifier_policy::*;
 pub use trust_policy_type::*;
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::{SecureChannelTrustInfo, TrustPolicy};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use ockam_core::compat::sync::Arc;
+    use ockam_core::{async_trait, Result};
+
+    /// Trust policy returning a fixed result and counting how many times it is checked
+    pub(crate) struct CountingTrustPolicyStub {
+        result: bool,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl CountingTrustPolicyStub {
+        pub(crate) fn create(result: bool, calls: Arc<AtomicUsize>) -> Arc<dyn TrustPolicy> {
+            Arc::new(Self { result, calls })
+        }
+    }
+
+    #[async_trait]
+    impl TrustPolicy for CountingTrustPolicyStub {
+        async fn check(&self, _trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.result)
+        }
+    }
+}
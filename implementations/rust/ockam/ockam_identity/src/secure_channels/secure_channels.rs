@@ -117,6 +117,7 @@ impl SecureChannels {
             options.trust_context,
             Some(route),
             Some(options.timeout),
+            None,
             Role::Initiator,
         )
         .await?;
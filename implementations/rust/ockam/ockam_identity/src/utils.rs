@@ -2,26 +2,42 @@ use minicbor::bytes::ByteVec;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "std")]
+use time::OffsetDateTime;
 
 use crate::models::{Attributes, CredentialSchemaIdentifier, TimestampInSeconds};
 use crate::IdentityError;
 
 /// Create a new timestamp using the system time
-#[cfg(feature = "std")]
 pub fn now() -> Result<TimestampInSeconds> {
-    if let Ok(now) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+    now_seconds()
+}
+
+/// Create a new timestamp, in seconds since the unix epoch, using the system time
+#[cfg(feature = "std")]
+pub fn now_seconds() -> Result<TimestampInSeconds> {
+    if let Ok(now) = SystemTime::now().duration_since(UNIX_EPOCH) {
         Ok(TimestampInSeconds(now.as_secs()))
     } else {
         Err(IdentityError::UnknownTimestamp.into())
     }
 }
 
-/// Create a new timestamp using the system time
+/// Create a new timestamp, in seconds since the unix epoch, using the system time
 #[cfg(not(feature = "std"))]
-pub fn now() -> Result<TimestampInSeconds> {
+pub fn now_seconds() -> Result<TimestampInSeconds> {
     Err(IdentityError::UnknownTimestamp.into())
 }
 
+/// Return the current UTC date time, using the same clock as [`now_seconds`]
+/// so that both kinds of timestamps can be compared
+#[cfg(feature = "std")]
+pub fn now_offset() -> OffsetDateTime {
+    OffsetDateTime::from(SystemTime::now())
+}
+
 /// Add a number of seconds to the [`TimestampInSeconds`]
 pub fn add_seconds(timestamp: &TimestampInSeconds, seconds: u64) -> TimestampInSeconds {
     TimestampInSeconds(timestamp.saturating_add(seconds))
@@ -57,3 +73,16 @@ impl AttributesBuilder {
         }
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_now_seconds_and_now_offset_agree() {
+        let seconds = now_seconds().unwrap();
+        let offset = now_offset();
+        let difference = offset.unix_timestamp() - *seconds as i64;
+        assert!((0..=1).contains(&difference), "difference: {difference}s");
+    }
+}
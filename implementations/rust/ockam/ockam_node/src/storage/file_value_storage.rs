@@ -3,13 +3,26 @@ use crate::tokio::task::{self, JoinError};
 use cfg_if::cfg_if;
 use fs2::FileExt; //locking
 use ockam_core::compat::boxed::Box;
+use ockam_core::env::get_env_with_default;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{async_trait, Error, Result};
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::BufReader;
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Environment variable used to set the maximum time, in seconds,
+/// to wait for the lock on a storage file
+pub const OCKAM_STORAGE_LOCK_TIMEOUT: &str = "OCKAM_STORAGE_LOCK_TIMEOUT";
+
+/// Default maximum time to wait for the lock on a storage file
+const DEFAULT_STORAGE_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delay between two attempts to acquire the lock on a storage file
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(10);
 
 /// File Storage
 /// There three files involved
@@ -21,12 +34,15 @@ use std::path::{Path, PathBuf};
 ///  - a lock file.  It's used to control inter-process accesses to the data.
 ///    Before reading or writing to the data fil, a shared or exclusive lock is first acquired
 ///    on this file.  We don't lock over the data file directly, because doesn't play well with
-///    the file rename we do
+///    the file rename we do.
+///    If the lock can't be acquired before the lock timeout, for example because another process
+///    holds it, the operation fails with a timeout error instead of blocking forever
 #[derive(Clone)]
 pub struct FileValueStorage<V> {
     path: Box<Path>,
     temp_path: Box<Path>,
     lock_path: Box<Path>,
+    lock_timeout: Duration,
     _phantom_data: PhantomData<V>,
 }
 
@@ -46,10 +62,17 @@ impl<V: Default + Serialize + for<'de> Deserialize<'de>> FileValueStorage<V> {
             path: path.into(),
             temp_path: temp_path.into(),
             lock_path: lock_path.into(),
+            lock_timeout: default_lock_timeout(),
             _phantom_data: PhantomData,
         }
     }
 
+    /// Set the maximum time to wait for the lock on the storage file
+    pub fn with_lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
+    }
+
     /// Create FileStorage using file at given Path
     /// If file doesn't exist, it will be created
     async fn init(&mut self) -> Result<()> {
@@ -58,9 +81,7 @@ impl<V: Default + Serialize + for<'de> Deserialize<'de>> FileValueStorage<V> {
         // This can block, but only when first initializing and just need to write an empty vault.
         // So didn't bother to do it async
         let lock_file = Self::open_lock_file(&self.lock_path)?;
-        lock_file
-            .lock_exclusive()
-            .map_err(|e| map_io_err(&self.lock_path, e))?;
+        lock_with_timeout(&lock_file, &self.lock_path, true, self.lock_timeout)?;
 
         let should_flush_default = if self.path.exists() {
             let metadata = self
@@ -140,6 +161,72 @@ impl<V> FileValueStorage<V> {
     }
 }
 
+/// Return the lock timeout configured with the `OCKAM_STORAGE_LOCK_TIMEOUT` environment variable
+fn default_lock_timeout() -> Duration {
+    get_env_with_default(
+        OCKAM_STORAGE_LOCK_TIMEOUT,
+        DEFAULT_STORAGE_LOCK_TIMEOUT.as_secs(),
+    )
+    .map(Duration::from_secs)
+    .unwrap_or(DEFAULT_STORAGE_LOCK_TIMEOUT)
+}
+
+/// Acquire a shared or exclusive lock on the lock file, retrying until the timeout elapses
+fn lock_with_timeout(file: &File, path: &Path, exclusive: bool, timeout: Duration) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let result = if exclusive {
+            file.try_lock_exclusive()
+        } else {
+            file.try_lock_shared()
+        };
+        match result {
+            Ok(()) => return Ok(()),
+            Err(e) if e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => {
+                if Instant::now() >= deadline {
+                    let error = StorageLockTimeout {
+                        lock_path: path.to_path_buf(),
+                        timeout,
+                    };
+                    return Err(Error::new(Origin::Node, Kind::Timeout, error));
+                }
+                std::thread::sleep(LOCK_RETRY_INTERVAL);
+            }
+            Err(e) => return Err(map_io_err(path, e)),
+        }
+    }
+}
+
+/// Cause of the error returned when the lock on a storage file can't be acquired
+/// before the lock timeout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageLockTimeout {
+    /// Path of the lock file
+    pub lock_path: PathBuf,
+    /// Time spent waiting for the lock
+    pub timeout: Duration,
+}
+
+impl StorageLockTimeout {
+    /// Return the lock timeout which caused the given error, if any
+    pub fn find(error: &Error) -> Option<&StorageLockTimeout> {
+        use std::error::Error as _;
+        error.source()?.downcast_ref::<StorageLockTimeout>()
+    }
+}
+
+impl Display for StorageLockTimeout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "timed out after {:?} waiting for the lock on {:?}",
+            self.timeout, self.lock_path
+        )
+    }
+}
+
+impl std::error::Error for StorageLockTimeout {}
+
 #[async_trait]
 impl<V: Default + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static> ValueStorage<V>
     for FileValueStorage<V>
@@ -157,9 +244,10 @@ impl<V: Default + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static> V
         let lock_path = self.lock_path.clone();
         let temp_path = self.temp_path.clone();
         let path = self.path.clone();
+        let lock_timeout = self.lock_timeout;
         let tr = move || -> Result<R> {
             let file = FileValueStorage::<V>::open_lock_file(&lock_path)?;
-            file.lock_exclusive().map_err(|e| map_io_err(&path, e))?;
+            lock_with_timeout(&file, &lock_path, true, lock_timeout)?;
             let existing_value = FileValueStorage::<V>::load(&path)?;
             let (updated_value, result) = f(existing_value)?;
             FileValueStorage::<V>::flush_to_file(&path, &temp_path, &updated_value)?;
@@ -176,9 +264,10 @@ impl<V: Default + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static> V
     ) -> Result<R> {
         let path = self.path.clone();
         let lock_path = self.lock_path.clone();
+        let lock_timeout = self.lock_timeout;
         let tr = move || {
             let file = FileValueStorage::<V>::open_lock_file(&lock_path)?;
-            file.lock_shared().map_err(|e| map_io_err(&path, e))?;
+            lock_with_timeout(&file, &lock_path, false, lock_timeout)?;
             let data = FileValueStorage::<V>::load(&path)?;
             let r = f(data)?;
             // if something goes wrong it will be unlocked once the file handler get closed anyway
@@ -282,6 +371,37 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_lock_timeout() -> Result<()> {
+        let path = NamedTempFile::new().unwrap();
+        let storage = FileValueStorage::<Value>::create(path.path())
+            .await?
+            .with_lock_timeout(Duration::from_millis(100));
+
+        // another file handle, like another process, holds the lock
+        let lock_path = FileValueStorage::<Value>::path_with_suffix(path.path(), "lock");
+        let holder = FileValueStorage::<Value>::open_lock_file(&lock_path)?;
+        holder.lock_exclusive().unwrap();
+
+        // the storage operations time out instead of blocking forever
+        let expected = StorageLockTimeout {
+            lock_path,
+            timeout: Duration::from_millis(100),
+        };
+        let error = storage.update_value(Ok).await.unwrap_err();
+        assert_eq!(error.code().kind, Kind::Timeout);
+        assert_eq!(StorageLockTimeout::find(&error), Some(&expected));
+        let error = storage.read_value(Ok).await.unwrap_err();
+        assert_eq!(error.code().kind, Kind::Timeout);
+        assert_eq!(StorageLockTimeout::find(&error), Some(&expected));
+
+        // once the lock is released the storage can be used again
+        holder.unlock().unwrap();
+        storage.update_value(|_| Ok(Value(1))).await?;
+        assert_eq!(storage.read_value(Ok).await?, Value(1));
+        Ok(())
+    }
+
     #[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug)]
     struct Value(u8);
 }